@@ -0,0 +1,101 @@
+//! `cargo bench --bench parse`, parse generated programs of growing size and print the
+//! time per line. The time per line must stay flat when the program get bigger, the
+//! benchmark fail when a program 8 times bigger is much slower per line
+
+use std::time::{Duration, Instant};
+
+use vipl::parse;
+
+/// `lines` lines of declarations, assignments, calls and blocks like a real program
+fn generate_program(lines: usize) -> String {
+    let mut result = String::new();
+    let mut i = 0;
+    let mut written = 0;
+    while written < lines {
+        result.push_str(&format!("var a{} = {} + 2 * (3 - {})\n", i, i, i));
+        result.push_str(&format!("a{} = a{} * 2 + độ_dài(\"xin chào\")\n", i, i));
+        result.push_str(&format!("in: a{}, \"\\n\" # in ra\n", i));
+        result.push_str(if i % 2 == 0 { "khối\n" } else { "hết\n" });
+        written += 4;
+        i += 1;
+    }
+    if i % 2 == 1 {
+        result.push_str("hết\n");
+    }
+    result
+}
+
+/// one statement with an expression of `terms` terms: `var x = 0 + 1 + 2 ...`
+fn generate_expression(terms: usize) -> String {
+    let terms = (0..terms).map(|i| i.to_string()).collect::<Vec<_>>();
+    format!("var x = {}\n", terms.join(" + "))
+}
+
+/// the best of a few runs, the first one also warm the allocator up
+fn measure(source: &str, config: &parse::KeywordConfig) -> Duration {
+    (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            let ast = parse::parse_string_to_ast(source.to_string(), config);
+            let elapsed = start.elapsed();
+            assert!(ast.is_ok(), "chương trình sinh ra phải đúng cú pháp");
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
+
+/// a parser quadratic in the size of the input take 8 times longer per line on a program
+/// 8 times bigger, a linear one the same time give or take the noise of the machine
+const MAX_SLOWDOWN: f64 = 3.0;
+
+/// print the time per unit at each size and stop the benchmark when the biggest size is
+/// more than `MAX_SLOWDOWN` times slower per unit than the smallest one
+fn check_linear(unit: &str, sizes: &[usize], generate: impl Fn(usize) -> String) {
+    let config = load_pack();
+    let per_unit = sizes
+        .iter()
+        .map(|&size| {
+            let time = measure(&generate(size), &config);
+            let per_unit = time.as_nanos() as f64 / size as f64;
+            println!(
+                "{:>7} {}: {:>8.2} ms, {:>6.0} ns/{}",
+                size,
+                unit,
+                time.as_secs_f64() * 1000.0,
+                per_unit,
+                unit
+            );
+            per_unit
+        })
+        .collect::<Vec<_>>();
+    let slowdown = per_unit.last().unwrap() / per_unit.first().unwrap();
+    if slowdown > MAX_SLOWDOWN {
+        eprintln!(
+            "lỗi: thời gian cho mỗi {} tăng {:.1} lần khi đầu vào lớn gấp {} lần, việc đọc chương trình không còn tuyến tính",
+            unit,
+            slowdown,
+            sizes.last().unwrap() / sizes.first().unwrap()
+        );
+        std::process::exit(1);
+    }
+}
+
+fn load_pack() -> parse::KeywordConfig {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../config/packs/vi.toml");
+    parse::load_config(&path).unwrap_or_else(|errors| {
+        for e in &errors {
+            eprintln!("{}", e);
+        }
+        std::process::exit(1)
+    })
+}
+
+fn main() {
+    check_linear("dòng", &[12_500, 25_000, 50_000, 100_000], generate_program);
+    check_linear(
+        "số hạng",
+        &[5_000, 10_000, 20_000, 40_000],
+        generate_expression,
+    );
+}
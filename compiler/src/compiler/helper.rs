@@ -4,12 +4,17 @@ use inkwell::{
     builder::Builder,
     context::Context,
     module::Module,
-    values::{BasicValueEnum, FunctionValue, PointerValue},
+    types::IntType,
+    values::{BasicValueEnum, FunctionValue, IntValue, PointerValue},
+    FloatPredicate, IntPredicate,
 };
 
-use crate::parse::{AstNode, AstNodeValue, KeywordConfig, Operation};
+use crate::parse::{
+    format_decimal, parse_decimal_literal, Ast, BinaryOp, BuiltinFunction, Call, Expr, ExprId,
+    ExprKind, KeywordConfig, RoundingMode, ValueType, DECIMAL_SCALE,
+};
 
-use super::VariableMetaType;
+use super::{runtime::Runtime, CompileOption, VariableMetaType};
 
 pub struct CompilerHelper;
 impl CompilerHelper {
@@ -20,15 +25,15 @@ impl CompilerHelper {
         name: &str,
     ) -> PointerValue<'a> {
         let i8_types = context.i8_type();
+        // copy the NUL at the end too, the runtime library read C strings
         let string_size = context
             .i64_type()
-            .const_int(string.len().try_into().unwrap(), true);
+            .const_int((string.len() + 1).try_into().unwrap(), true);
         let string_value = builder
             .build_array_malloc(i8_types, string_size.clone(), name)
             .unwrap();
 
-        let global_string_value =
-            builder.build_global_string_ptr(&string.replace("\\n", "\n"), "str");
+        let global_string_value = builder.build_global_string_ptr(&string, "str");
         builder
             .build_memcpy(
                 string_value,
@@ -41,19 +46,6 @@ impl CompilerHelper {
 
         return string_value;
     }
-    pub fn create_number_variable<'a>(
-        context: &'a Context,
-        builder: &Builder<'a>,
-        value: f64,
-        name: &str,
-    ) -> PointerValue<'a> {
-        if value.fract() == 0.0 {
-            Self::create_int_variable(context, builder, value, name)
-        } else {
-            Self::create_float_variable(context, builder, value, name)
-        }
-    }
-
     pub fn create_float_variable<'a>(
         context: &'a Context,
         builder: &Builder<'a>,
@@ -66,29 +58,272 @@ impl CompilerHelper {
 
         return string_value;
     }
-    pub fn create_int_variable<'a>(
+    pub fn const_number<'a>(context: &'a Context, value: &ExprKind) -> BasicValueEnum<'a> {
+        match value {
+            ExprKind::Int(i) => {
+                BasicValueEnum::IntValue(context.i64_type().const_int(*i as u64, true))
+            }
+            ExprKind::Float(f) => BasicValueEnum::FloatValue(context.f64_type().const_float(*f)),
+            ExprKind::Decimal(d) => {
+                BasicValueEnum::IntValue(context.i64_type().const_int(*d as u64, true))
+            }
+            _ => unreachable!(),
+        }
+    }
+    pub fn int_type<'a>(context: &'a Context, value_type: ValueType) -> IntType<'a> {
+        match value_type.int_info().unwrap().0 {
+            8 => context.i8_type(),
+            16 => context.i16_type(),
+            32 => context.i32_type(),
+            _ => context.i64_type(),
+        }
+    }
+    /// convert a value between two number types, like `as` in rust: float to int round
+    /// toward zero and a narrower int keep only the low bits. A `thập_phân` is also an
+    /// i64 so `from` tell it apart from an integer
+    pub fn cast_value<'a>(
         context: &'a Context,
         builder: &Builder<'a>,
-        value: f64,
-        name: &str,
-    ) -> PointerValue<'a> {
-        let i64_types = context.i64_type();
-        let string_value = builder.build_alloca(i64_types, name);
-        builder.build_store(
-            string_value,
-            context
-                .i64_type()
-                .const_int_from_string(&value.to_string(), inkwell::types::StringRadix::Decimal)
-                .unwrap(),
+        module: &Module<'a>,
+        value: BasicValueEnum<'a>,
+        from: Option<ValueType>,
+        to: Option<ValueType>,
+    ) -> BasicValueEnum<'a> {
+        let from_signed = from.and_then(|t| t.int_info()).is_none_or(|(_, s)| s);
+        let from_decimal = from == Some(ValueType::Decimal);
+        match (value, to) {
+            (_, Some(ValueType::Decimal)) if from_decimal => value,
+            (BasicValueEnum::IntValue(i), Some(ValueType::Decimal)) => {
+                // a literal is converted now, the overflow of a variable is checked at runtime
+                let constant = if from_signed {
+                    i.get_sign_extended_constant()
+                        .and_then(|c| c.checked_mul(DECIMAL_SCALE))
+                } else {
+                    i.get_zero_extended_constant()
+                        .and_then(|c| i64::try_from(c).ok())
+                        .and_then(|c| c.checked_mul(DECIMAL_SCALE))
+                };
+                BasicValueEnum::IntValue(match constant {
+                    Some(c) => context.i64_type().const_int(c as u64, true),
+                    None => {
+                        let wide = Self::cast_value(
+                            context,
+                            builder,
+                            module,
+                            value,
+                            from,
+                            Some(if from_signed {
+                                ValueType::Int
+                            } else {
+                                ValueType::UInt64
+                            }),
+                        );
+                        Runtime::decimal_from_int(
+                            context,
+                            builder,
+                            module,
+                            wide.into_int_value(),
+                            from_signed,
+                        )
+                    }
+                })
+            }
+            (BasicValueEnum::FloatValue(f), Some(ValueType::Decimal)) => {
+                BasicValueEnum::IntValue(Runtime::decimal_from_float(context, builder, module, f))
+            }
+            (BasicValueEnum::IntValue(i), Some(ValueType::Float)) if from_decimal => {
+                BasicValueEnum::FloatValue(Runtime::decimal_to_float(context, builder, module, i))
+            }
+            (BasicValueEnum::IntValue(i), Some(t)) if from_decimal && t.is_int() => {
+                let value = Runtime::decimal_to_int(context, builder, module, i);
+                Self::cast_value(
+                    context,
+                    builder,
+                    module,
+                    value.into(),
+                    Some(ValueType::Int),
+                    to,
+                )
+            }
+            (BasicValueEnum::IntValue(i), Some(ValueType::Float)) => {
+                BasicValueEnum::FloatValue(if from_signed {
+                    builder.build_signed_int_to_float(i, context.f64_type(), "to_float")
+                } else {
+                    builder.build_unsigned_int_to_float(i, context.f64_type(), "to_float")
+                })
+            }
+            (BasicValueEnum::FloatValue(f), Some(t)) if t.is_int() => {
+                let int_type = Self::int_type(context, t);
+                BasicValueEnum::IntValue(if t.int_info().unwrap().1 {
+                    builder.build_float_to_signed_int(f, int_type, "to_int")
+                } else {
+                    builder.build_float_to_unsigned_int(f, int_type, "to_int")
+                })
+            }
+            (BasicValueEnum::IntValue(i), Some(t)) if t.is_int() => {
+                let int_type = Self::int_type(context, t);
+                let from_bits = i.get_type().get_bit_width();
+                let to_bits = int_type.get_bit_width();
+                BasicValueEnum::IntValue(if from_bits > to_bits {
+                    builder.build_int_truncate(i, int_type, "to_int")
+                } else if from_bits < to_bits && from_signed {
+                    builder.build_int_s_extend(i, int_type, "to_int")
+                } else if from_bits < to_bits {
+                    builder.build_int_z_extend(i, int_type, "to_int")
+                } else {
+                    i
+                })
+            }
+            _ => value,
+        }
+    }
+    /// printf read variadic integers as C `int` or `long long`, a narrower value must be
+    /// extended first or the high bits are garbage
+    pub fn printf_int<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+        value_type: Option<ValueType>,
+    ) -> (&'static str, BasicValueEnum<'a>) {
+        let (bits, signed) = value_type
+            .and_then(|t| t.int_info())
+            .unwrap_or((value.get_type().get_bit_width(), true));
+        if bits > 32 {
+            (if signed { "%lld" } else { "%llu" }, value.into())
+        } else if signed {
+            let value = Self::cast_value(
+                context,
+                builder,
+                module,
+                value.into(),
+                Some(ValueType::Int8),
+                Some(ValueType::Int32),
+            );
+            ("%d", value)
+        } else {
+            let value = Self::cast_value(
+                context,
+                builder,
+                module,
+                value.into(),
+                Some(ValueType::UInt8),
+                Some(ValueType::UInt32),
+            );
+            ("%u", value)
+        }
+    }
+    /// printf format and argument of a value, a `thập_phân` or a `danh_sách` is printed as
+    /// text made by the runtime library. The text that must be freed after printing is
+    /// pushed to `temporary`
+    pub fn printf_value<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: BasicValueEnum<'a>,
+        value_type: Option<ValueType>,
+        temporary: &mut Vec<PointerValue<'a>>,
+    ) -> (&'static str, BasicValueEnum<'a>) {
+        match value {
+            BasicValueEnum::IntValue(i) if value_type == Some(ValueType::Decimal) => (
+                "%s",
+                Runtime::decimal_format(context, builder, module, i).into(),
+            ),
+            BasicValueEnum::IntValue(i) if value_type == Some(ValueType::Bool) => {
+                let true_text = builder.build_global_string_ptr("true", "true_text");
+                let false_text = builder.build_global_string_ptr("false", "false_text");
+                (
+                    "%s",
+                    builder.build_select(
+                        i,
+                        true_text.as_pointer_value(),
+                        false_text.as_pointer_value(),
+                        "bool_text",
+                    ),
+                )
+            }
+            BasicValueEnum::PointerValue(p) if value_type == Some(ValueType::List) => {
+                let text = Runtime::list_format(context, builder, module, p);
+                temporary.push(text);
+                ("%s", text.into())
+            }
+            BasicValueEnum::IntValue(i) => {
+                Self::printf_int(context, builder, module, i, value_type)
+            }
+            BasicValueEnum::FloatValue(_) => ("%f", value),
+            BasicValueEnum::PointerValue(_) => ("%s", value),
+            _ => unreachable!("một giá trị là số, logic hoặc con trỏ"),
+        }
+    }
+    /// stop the program with `message` when `failed` is true, the code after this
+    /// continue in a new block that is only reached when the check passed
+    pub fn build_runtime_check<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        failed: IntValue<'a>,
+        message: &str,
+    ) {
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let error_block = context.append_basic_block(function, "runtime_error");
+        let ok_block = context.append_basic_block(function, "runtime_ok");
+        builder.build_conditional_branch(failed, error_block, ok_block);
+
+        builder.position_at_end(error_block);
+        let message = format!("{}\n", message);
+        let message_ptr = builder.build_global_string_ptr(&message, "runtime_error_message");
+        builder.build_call(
+            DefaultFunction::get_write_function(context, module),
+            &[
+                context.i32_type().const_int(2, false).into(),
+                message_ptr.as_pointer_value().into(),
+                context
+                    .i64_type()
+                    .const_int(message.len() as u64, false)
+                    .into(),
+            ],
+            "write_error",
+        );
+        builder.build_call(
+            DefaultFunction::get_exit_function(context, module),
+            &[context.i32_type().const_int(1, false).into()],
+            "exit",
         );
+        builder.build_unreachable();
 
-        return string_value;
+        builder.position_at_end(ok_block);
+    }
+    pub fn create_constant_string<'a>(
+        builder: &Builder<'a>,
+        string: String,
+        name: &str,
+    ) -> PointerValue<'a> {
+        builder
+            .build_global_string_ptr(&string, name)
+            .as_pointer_value()
+    }
+    pub fn create_constant_number<'a>(
+        module: &Module<'a>,
+        value: BasicValueEnum<'a>,
+        name: &str,
+    ) -> PointerValue<'a> {
+        let global = match value {
+            BasicValueEnum::IntValue(i) => module.add_global(i.get_type(), None, name),
+            BasicValueEnum::FloatValue(f) => module.add_global(f.get_type(), None, name),
+            _ => unreachable!("chỉ số được gấp thành hằng khi dịch"),
+        };
+        global.set_linkage(inkwell::module::Linkage::Private);
+        global.set_initializer(&value);
+        global.set_constant(true);
+        global.as_pointer_value()
     }
 }
 
 pub struct DefaultFunction;
 
 static PRINT_FN_NAME: &str = "printf";
+static WRITE_FN_NAME: &str = "write";
+static EXIT_FN_NAME: &str = "exit";
 
 impl DefaultFunction {
     pub fn get_default_function<'a>(
@@ -113,6 +348,44 @@ impl DefaultFunction {
             }
         }
     }
+    pub fn get_write_function<'a>(context: &'a Context, module: &Module<'a>) -> FunctionValue<'a> {
+        match module.get_function(WRITE_FN_NAME) {
+            Some(f) => f,
+            None => {
+                let write_fn_type = context.i64_type().fn_type(
+                    &[
+                        context.i32_type().into(),
+                        context
+                            .i8_type()
+                            .ptr_type(inkwell::AddressSpace::Generic)
+                            .into(),
+                        context.i64_type().into(),
+                    ],
+                    false,
+                );
+                module.add_function(
+                    WRITE_FN_NAME,
+                    write_fn_type,
+                    Some(inkwell::module::Linkage::External),
+                )
+            }
+        }
+    }
+    pub fn get_exit_function<'a>(context: &'a Context, module: &Module<'a>) -> FunctionValue<'a> {
+        match module.get_function(EXIT_FN_NAME) {
+            Some(f) => f,
+            None => {
+                let exit_fn_type = context
+                    .void_type()
+                    .fn_type(&[context.i32_type().into()], false);
+                module.add_function(
+                    EXIT_FN_NAME,
+                    exit_fn_type,
+                    Some(inkwell::module::Linkage::External),
+                )
+            }
+        }
+    }
 }
 
 pub struct ParseExpr;
@@ -123,19 +396,14 @@ impl ParseExpr {
         module: &Module<'a>,
         variable: &BTreeMap<String, PointerValue<'a>>,
         variable_metadata: &BTreeMap<String, VariableMetaType>,
-        function_name_dist: &BTreeMap<String, String>,
         line: usize,
         config: &KeywordConfig,
-        command: &AstNode,
+        option: &CompileOption,
+        ast: &Ast,
+        call: &Call,
     ) -> inkwell::values::CallSiteValue<'a> {
-        let function_name = command.left[0]
-            .op
-            .get_ident_value()
-            .unwrap()
-            .get_function_name()
-            .unwrap();
-        let function_args = command.right.clone();
-        if let Some(a) = config.default_function.get(&function_name) {
+        let function_name = &call.function;
+        if let Some(a) = config.default_function.get(function_name) {
             if a.r#type == "print" {
                 return Self::parse_call_print_function_syntax(
                     context,
@@ -144,93 +412,14 @@ impl ParseExpr {
                     variable,
                     variable_metadata,
                     line,
-                    function_name,
-                    function_args,
+                    config,
+                    option,
+                    ast,
+                    &call.args,
                 );
-            } else {
-                todo!()
-            }
-        } else if let Some(name) = function_name_dist.get(&function_name) {
-            if let Some(function) = module.get_function(name) {
-                let mut call_args = vec![];
-                let mut must_remove = vec![];
-                for args in function_args {
-                    let value = args
-                        .op
-                        .get_value_value()
-                        .unwrap()
-                        .get_function_args()
-                        .unwrap();
-                    match value {
-                        crate::parse::AstNodeValue::String(s) => {
-                            let ptr = CompilerHelper::create_sting_variable(
-                                context,
-                                builder,
-                                s.clone(),
-                                "tmp",
-                            );
-                            call_args
-                                .push(inkwell::values::BasicMetadataValueEnum::PointerValue(ptr));
-                            must_remove.push(ptr);
-                        }
-                        crate::parse::AstNodeValue::Number(num) => {
-                            if num.fract() == 0.0 {
-                                call_args.push(inkwell::values::BasicMetadataValueEnum::IntValue(
-                                    context
-                                        .i64_type()
-                                        .const_int_from_string(
-                                            &num.to_string(),
-                                            inkwell::types::StringRadix::Decimal,
-                                        )
-                                        .unwrap(),
-                                ))
-                            } else {
-                                call_args.push(
-                                    inkwell::values::BasicMetadataValueEnum::FloatValue(
-                                        context.f64_type().const_float(num),
-                                    ),
-                                );
-                            }
-                        }
-                        crate::parse::AstNodeValue::None => todo!(),
-                        crate::parse::AstNodeValue::Bool(b) => {
-                            call_args.push(inkwell::values::BasicMetadataValueEnum::IntValue(
-                                context.bool_type().const_int(b as u64, true),
-                            ));
-                        }
-                        crate::parse::AstNodeValue::Variable(name) => {
-                            if let Some(v) = variable.get(&name) {
-                                let variable_type = variable_metadata.get(&name).unwrap();
-                                match variable_type {
-                                    VariableMetaType::String => {
-                                        call_args.push(
-                                            inkwell::values::BasicMetadataValueEnum::PointerValue(
-                                                *v,
-                                            ),
-                                        );
-                                    }
-                                    VariableMetaType::Number => {
-                                        call_args.push(builder.build_load(*v, "").into());
-                                    }
-                                }
-                            } else {
-                                panic!("code kiểu beep gì vậy đã ko phải là biến mặc định rồi mà còn dùng biến ko được define(khai báo đó nếu ko bik từ này thì nên học thêm từ undefined đi) lỗi tại dòng này nè {} lo đi mà sửa đi",line);
-                            }
-                        }
-                        AstNodeValue::Operation(_) => todo!(),
-                    }
-                }
-                let result = builder.build_call(function, &call_args, "function_return");
-                for ptr in must_remove {
-                    builder.build_free(ptr);
-                }
-                return result;
-            } else {
-                todo!()
             }
-        } else {
-            todo!()
         }
+        unreachable!("chỉ lệnh in được dùng làm câu lệnh, bị bộ phân giải tên từ chối")
     }
     fn parse_call_print_function_syntax<'a>(
         context: &'a Context,
@@ -239,72 +428,92 @@ impl ParseExpr {
         variable: &BTreeMap<String, PointerValue<'a>>,
         variable_metadata: &BTreeMap<String, VariableMetaType>,
         line: usize,
-        _fn_name: String,
-        fn_args: Vec<AstNode>,
+        config: &KeywordConfig,
+        option: &CompileOption,
+        ast: &Ast,
+        fn_args: &[ExprId],
     ) -> inkwell::values::CallSiteValue<'a> {
         let print_fn = DefaultFunction::get_default_function(context, module);
         let mut print_value = "".to_string();
         let mut print_args = vec![];
-        for args in fn_args {
-            let value = args
-                .op
-                .get_value_value()
-                .unwrap()
-                .get_function_args()
-                .unwrap();
-            match value {
-                crate::parse::AstNodeValue::String(s) => {
-                    print_value.push_str(&s);
+        let mut must_remove = vec![];
+        let mut must_remove_list = vec![];
+        for args in fn_args.iter().map(|a| &ast[*a]) {
+            match &args.kind {
+                ExprKind::String(s) => {
+                    // the text is part of the printf format
+                    print_value.push_str(&s.replace('%', "%%"));
                 }
-                crate::parse::AstNodeValue::Number(num) => {
+                ExprKind::Int(num) => {
                     print_value.push_str(&num.to_string());
                 }
-                crate::parse::AstNodeValue::None => {
-                    todo!()
+                ExprKind::Float(num) => {
+                    print_value.push_str(&format!("{:?}", num));
                 }
-                crate::parse::AstNodeValue::Bool(b) => {
+                ExprKind::Decimal(num) => {
+                    print_value.push_str(&format_decimal(*num));
+                }
+                ExprKind::Bool(b) => {
                     print_value.push_str(&b.to_string());
                 }
-                crate::parse::AstNodeValue::Variable(name) => {
-                    if let Some(v) = variable.get(&name) {
-                        let variable_type = variable_metadata.get(&name).unwrap();
-                        match variable_type {
-                            VariableMetaType::String => {
-                                print_value.push_str("%s");
-                                print_args.push(inkwell::values::BasicValueEnum::PointerValue(*v));
-                            }
-                            VariableMetaType::Number => {
-                                if v.get_type().get_element_type().is_int_type() {
-                                    print_value.push_str("%d");
-                                } else if v.get_type().get_element_type().is_float_type() {
-                                    print_value.push_str("%f");
-                                } else {
-                                    todo!()
-                                }
-                                print_args.push(builder.build_load(*v, ""));
-                            }
-                        }
+                ExprKind::Variable(name) => {
+                    if let Some(v) = variable.get(name) {
+                        let variable_type = variable_metadata.get(name).unwrap();
+                        let value = match variable_type {
+                            VariableMetaType::String => BasicValueEnum::PointerValue(*v),
+                            VariableMetaType::Number => builder.build_load(*v, ""),
+                        };
+                        let (format, value) = CompilerHelper::printf_value(
+                            context,
+                            builder,
+                            module,
+                            value,
+                            args.value_type,
+                            &mut must_remove,
+                        );
+                        print_value.push_str(format);
+                        print_args.push(value);
                     } else {
                         panic!("code kiểu beep gì vậy đã ko phải là biến mặc định rồi mà còn dùng biến ko được define(khai báo đó nếu ko bik từ này thì nên học thêm từ undefined đi) lỗi tại dòng này nè {} lo đi mà sửa đi",line);
                     }
                 }
-                AstNodeValue::Operation(o) => {
-                    let value =
-                        compile_math_operation(context, builder, variable, variable_metadata, &o);
+                ExprKind::Binary { .. }
+                | ExprKind::Call(_)
+                | ExprKind::Index { .. }
+                | ExprKind::Slice { .. } => {
+                    let value = compile_math_operation(
+                        context,
+                        builder,
+                        module,
+                        variable,
+                        variable_metadata,
+                        line,
+                        config,
+                        option,
+                        ast,
+                        args,
+                    );
+                    // a string or a list made by the runtime library only live until it is
+                    // printed
                     match value {
-                        BasicValueEnum::ArrayValue(_) => todo!(),
-                        BasicValueEnum::IntValue(_) => {
-                            print_value.push_str("%d");
-                            print_args.push(value.clone());
-                        }
-                        BasicValueEnum::FloatValue(_) => {
-                            print_value.push_str("%f");
-                            print_args.push(value.clone());
+                        BasicValueEnum::PointerValue(ptr)
+                            if args.value_type == Some(ValueType::List) =>
+                        {
+                            must_remove_list.push(ptr)
                         }
-                        BasicValueEnum::PointerValue(_) => todo!(),
-                        BasicValueEnum::StructValue(_) => todo!(),
-                        BasicValueEnum::VectorValue(_) => todo!(),
+                        BasicValueEnum::PointerValue(ptr) => must_remove.push(ptr),
+                        _ => {}
                     }
+                    let (format, value) = CompilerHelper::printf_value(
+                        context,
+                        builder,
+                        module,
+                        value,
+                        args.value_type,
+                        &mut must_remove,
+                    );
+                    print_value.push_str(format);
+                    print_args.push(value);
                 }
             }
         }
@@ -319,338 +528,1077 @@ impl ParseExpr {
         call_args.push(inkwell::values::BasicMetadataValueEnum::PointerValue(
             string_variable,
         ));
-        for a in print_args {
-            match a {
-                inkwell::values::BasicValueEnum::ArrayValue(_) => todo!(),
-                inkwell::values::BasicValueEnum::IntValue(v) => {
-                    call_args.push(inkwell::values::BasicMetadataValueEnum::IntValue(v));
-                }
-                inkwell::values::BasicValueEnum::FloatValue(f) => {
-                    call_args.push(inkwell::values::BasicMetadataValueEnum::FloatValue(f));
-                }
-                inkwell::values::BasicValueEnum::PointerValue(v) => {
-                    call_args.push(inkwell::values::BasicMetadataValueEnum::PointerValue(v));
-                }
-                inkwell::values::BasicValueEnum::StructValue(_) => todo!(),
-                inkwell::values::BasicValueEnum::VectorValue(_) => todo!(),
-            }
-        }
+        // `printf_value` only give numbers and pointers
+        call_args.extend(
+            print_args
+                .into_iter()
+                .map(inkwell::values::BasicMetadataValueEnum::from),
+        );
         let result = builder.build_call(print_fn, &call_args, "call_printf_fn");
         builder.build_free(string_variable);
+        for ptr in must_remove {
+            builder.build_free(ptr);
+        }
+        for ptr in must_remove_list {
+            Runtime::free(context, builder, module, ptr, Some(ValueType::List));
+        }
         result
     }
     pub fn parse_new_variable_syntax<'a>(
         context: &'a Context,
         builder: &Builder<'a>,
-        _module: &Module<'a>,
+        module: &Module<'a>,
         variable: &mut BTreeMap<String, PointerValue<'a>>,
         variable_metadata: &mut BTreeMap<String, VariableMetaType>,
-        _line: usize,
-        _config: &KeywordConfig,
-        command: &AstNode,
+        line: usize,
+        config: &KeywordConfig,
+        option: &CompileOption,
+        ast: &Ast,
+        variable_name: &str,
+        variable_value: Option<&Expr>,
+        value_type: Option<ValueType>,
     ) {
-        let variable_name = command.left[0]
-            .op
-            .get_ident_value()
-            .unwrap()
-            .get_variable_name()
-            .unwrap();
-        let variable_value = if command.right.len() > 0 {
-            Some(
-                command.right[0]
-                    .op
-                    .get_value_value()
-                    .unwrap()
-                    .get_variable_value()
-                    .unwrap(),
-            )
-        } else {
-            None
-        };
-
         match variable_value {
-            Some(v) => match v {
-                crate::parse::AstNodeValue::String(s) => {
-                    let ptr =
-                        CompilerHelper::create_sting_variable(context, &builder, s, &variable_name);
-                    variable.insert(variable_name.clone(), ptr);
-                    variable_metadata.insert(variable_name.clone(), VariableMetaType::String);
-                }
-                crate::parse::AstNodeValue::Number(n) => {
-                    let ptr = CompilerHelper::create_number_variable(
+            Some(v) => match &v.kind {
+                ExprKind::String(s) => {
+                    let ptr = CompilerHelper::create_sting_variable(
                         context,
                         &builder,
-                        n,
-                        &variable_name,
+                        s.clone(),
+                        variable_name,
+                    );
+                    variable.insert(variable_name.to_string(), ptr);
+                    variable_metadata.insert(variable_name.to_string(), VariableMetaType::String);
+                }
+                // the new variable own a copy, giving one of them a new string later must
+                // not free the other one
+                ExprKind::Variable(_)
+                    if matches!(
+                        v.value_type,
+                        Some(ValueType::String | ValueType::Char | ValueType::List)
+                    ) =>
+                {
+                    let value = compile_math_operation(
+                        context,
+                        builder,
+                        module,
+                        variable,
+                        variable_metadata,
+                        line,
+                        config,
+                        option,
+                        ast,
+                        v,
+                    );
+                    let ptr = Runtime::copy(
+                        context,
+                        builder,
+                        module,
+                        value.into_pointer_value(),
+                        v.value_type,
                     );
-                    variable.insert(variable_name.clone(), ptr);
-                    variable_metadata.insert(variable_name.clone(), VariableMetaType::Number);
+                    variable.insert(variable_name.to_string(), ptr);
+                    variable_metadata.insert(variable_name.to_string(), VariableMetaType::String);
                 }
-                crate::parse::AstNodeValue::None => todo!(),
-                crate::parse::AstNodeValue::Bool(_) => todo!(),
-                crate::parse::AstNodeValue::Variable(_) => todo!(),
-                crate::parse::AstNodeValue::Operation(op) => {
-                    let node =
-                        compile_math_operation(context, builder, variable, variable_metadata, &op);
-                    match node {
-                        BasicValueEnum::ArrayValue(_) => todo!(),
+                _ => {
+                    let value = compile_math_operation(
+                        context,
+                        builder,
+                        module,
+                        variable,
+                        variable_metadata,
+                        line,
+                        config,
+                        option,
+                        ast,
+                        v,
+                    );
+                    let value = CompilerHelper::cast_value(
+                        context,
+                        builder,
+                        module,
+                        value,
+                        v.value_type,
+                        value_type,
+                    );
+                    let ptr = match value {
                         BasicValueEnum::IntValue(i) => {
-                            let ptr = builder.build_alloca(context.i64_type(), &variable_name);
+                            let ptr = builder.build_alloca(i.get_type(), variable_name);
                             builder.build_store(ptr, i);
-
-                            variable.insert(variable_name.clone(), ptr);
-                            variable_metadata
-                                .insert(variable_name.clone(), VariableMetaType::Number);
+                            ptr
                         }
                         BasicValueEnum::FloatValue(f) => {
-                            let ptr = builder.build_alloca(context.f64_type(), &variable_name);
+                            let ptr = builder.build_alloca(context.f64_type(), variable_name);
                             builder.build_store(ptr, f);
-
-                            variable.insert(variable_name.clone(), ptr);
-                            variable_metadata
-                                .insert(variable_name.clone(), VariableMetaType::Number);
+                            ptr
+                        }
+                        // a new string from the runtime library, the variable own it
+                        BasicValueEnum::PointerValue(p) => p,
+                        _ => unreachable!("một giá trị là số, logic hoặc con trỏ"),
+                    };
+                    variable.insert(variable_name.to_string(), ptr);
+                    variable_metadata.insert(
+                        variable_name.to_string(),
+                        if value.is_pointer_value() {
+                            VariableMetaType::String
+                        } else {
+                            VariableMetaType::Number
                         },
-                        BasicValueEnum::PointerValue(_) => todo!(),
-                        BasicValueEnum::StructValue(_) => todo!(),
-                        BasicValueEnum::VectorValue(_) => todo!(),
-                    }
+                    );
                 }
             },
-            None => todo!(),
+            // `var x: số_nguyên`, the type checker only let a number, a bool or a string
+            // be declared without a value
+            None => {
+                let (ptr, metadata) = match value_type {
+                    Some(ValueType::String) => (
+                        CompilerHelper::create_sting_variable(
+                            context,
+                            builder,
+                            String::new(),
+                            variable_name,
+                        ),
+                        VariableMetaType::String,
+                    ),
+                    Some(ValueType::Float) => {
+                        let ptr = builder.build_alloca(context.f64_type(), variable_name);
+                        builder.build_store(ptr, context.f64_type().const_float(0.0));
+                        (ptr, VariableMetaType::Number)
+                    }
+                    Some(ValueType::Bool) => {
+                        let ptr = builder.build_alloca(context.bool_type(), variable_name);
+                        builder.build_store(ptr, context.bool_type().const_zero());
+                        (ptr, VariableMetaType::Number)
+                    }
+                    // a `thập_phân` is an i64 like `số_nguyên`
+                    Some(ValueType::Decimal) => {
+                        let ptr = builder.build_alloca(context.i64_type(), variable_name);
+                        builder.build_store(ptr, context.i64_type().const_zero());
+                        (ptr, VariableMetaType::Number)
+                    }
+                    Some(t) if t.is_int() => {
+                        let int_type = CompilerHelper::int_type(context, t);
+                        let ptr = builder.build_alloca(int_type, variable_name);
+                        builder.build_store(ptr, int_type.const_zero());
+                        (ptr, VariableMetaType::Number)
+                    }
+                    _ => unreachable!("bị kiểm tra kiểu từ chối"),
+                };
+                variable.insert(variable_name.to_string(), ptr);
+                variable_metadata.insert(variable_name.to_string(), metadata);
+            }
         }
     }
-    pub fn parse_set_variable_syntax<'a>(
+    pub fn parse_new_constant_syntax<'a>(
         context: &'a Context,
         builder: &Builder<'a>,
-        _module: &Module<'a>,
+        module: &Module<'a>,
         variable: &mut BTreeMap<String, PointerValue<'a>>,
         variable_metadata: &mut BTreeMap<String, VariableMetaType>,
         line: usize,
-        _config: &KeywordConfig,
-        command: &AstNode,
+        config: &KeywordConfig,
+        option: &CompileOption,
+        ast: &Ast,
+        variable_name: &str,
+        variable_value: &Expr,
+        value_type: Option<ValueType>,
     ) {
-        let variable_name = command.left[0]
-            .op
-            .get_ident_value()
-            .unwrap()
-            .get_variable_name()
-            .unwrap();
-        let variable_value = command.right[0]
-            .op
-            .get_value_value()
-            .unwrap()
-            .get_variable_value()
-            .unwrap();
+        let global_name = format!("hằng.{}", variable_name);
 
+        match &variable_value.kind {
+            ExprKind::String(s) => {
+                let ptr = CompilerHelper::create_constant_string(builder, s.clone(), &global_name);
+                variable.insert(variable_name.to_string(), ptr);
+                variable_metadata.insert(variable_name.to_string(), VariableMetaType::String);
+            }
+            // the constant own a copy, the variable can still be given a new string
+            ExprKind::Variable(_)
+                if matches!(
+                    variable_value.value_type,
+                    Some(ValueType::String | ValueType::Char | ValueType::List)
+                ) =>
+            {
+                let value = compile_math_operation(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    variable_value,
+                );
+                let ptr = Runtime::copy(
+                    context,
+                    builder,
+                    module,
+                    value.into_pointer_value(),
+                    variable_value.value_type,
+                );
+                variable.insert(variable_name.to_string(), ptr);
+                variable_metadata.insert(variable_name.to_string(), VariableMetaType::String);
+            }
+            ExprKind::Int(_)
+            | ExprKind::Float(_)
+            | ExprKind::Decimal(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Variable(_)
+            | ExprKind::Binary { .. }
+            | ExprKind::Call(_)
+            | ExprKind::Index { .. }
+            | ExprKind::Slice { .. } => {
+                let value = compile_math_operation(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    variable_value,
+                );
+                let value = CompilerHelper::cast_value(
+                    context,
+                    builder,
+                    module,
+                    value,
+                    variable_value.value_type,
+                    value_type,
+                );
+                let ptr = match value {
+                    // every operand was a literal so the value was folded at compile time
+                    BasicValueEnum::IntValue(i) if i.is_const() => {
+                        CompilerHelper::create_constant_number(module, value, &global_name)
+                    }
+                    BasicValueEnum::FloatValue(f) if f.is_const() => {
+                        CompilerHelper::create_constant_number(module, value, &global_name)
+                    }
+                    // depends on a variable, the value is only known at runtime
+                    BasicValueEnum::IntValue(i) => {
+                        let ptr = builder.build_alloca(i.get_type(), variable_name);
+                        builder.build_store(ptr, i);
+                        ptr
+                    }
+                    BasicValueEnum::FloatValue(f) => {
+                        let ptr = builder.build_alloca(context.f64_type(), variable_name);
+                        builder.build_store(ptr, f);
+                        ptr
+                    }
+                    BasicValueEnum::PointerValue(p) => p,
+                    _ => unreachable!("một giá trị là số, logic hoặc con trỏ"),
+                };
+                variable.insert(variable_name.to_string(), ptr);
+                variable_metadata.insert(
+                    variable_name.to_string(),
+                    if value.is_pointer_value() {
+                        VariableMetaType::String
+                    } else {
+                        VariableMetaType::Number
+                    },
+                );
+            }
+        }
+    }
+    pub fn parse_set_variable_syntax<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        variable: &mut BTreeMap<String, PointerValue<'a>>,
+        variable_metadata: &mut BTreeMap<String, VariableMetaType>,
+        line: usize,
+        config: &KeywordConfig,
+        option: &CompileOption,
+        ast: &Ast,
+        variable_name: &str,
+        variable_value: &Expr,
+        value_type: Option<ValueType>,
+    ) {
         let ptr = variable
-            .get(&variable_name)
+            .get(variable_name)
             .expect(&("variable is not defined ".to_string() + &line.to_string()));
         let variable_type = variable_metadata
-            .get(&variable_name)
+            .get(variable_name)
             .expect(&("variable is not true type ".to_string() + &line.to_string()));
         match variable_type {
-            VariableMetaType::String => {
-                if let AstNodeValue::String(s) = variable_value {
+            VariableMetaType::String => match &variable_value.kind {
+                ExprKind::String(s) => {
                     builder.build_free(*ptr);
-                    let ptr =
-                        CompilerHelper::create_sting_variable(context, builder, s, &variable_name);
-                    variable.remove(&variable_name);
-                    variable.insert(variable_name.clone(), ptr);
-                } else {
-                    panic!(
-                        "{}",
-                        &("variable type is not valid type ".to_string() + &line.to_string())
-                    )
+                    let ptr = CompilerHelper::create_sting_variable(
+                        context,
+                        builder,
+                        s.clone(),
+                        variable_name,
+                    );
+                    variable.insert(variable_name.to_string(), ptr);
                 }
-            }
+                ExprKind::Binary { .. }
+                | ExprKind::Call(_)
+                | ExprKind::Index { .. }
+                | ExprKind::Slice { .. } => {
+                    // computed before the old string is freed, `s = s[1..]` still read it
+                    let old_ptr = *ptr;
+                    let value = compile_math_operation(
+                        context,
+                        builder,
+                        module,
+                        variable,
+                        variable_metadata,
+                        line,
+                        config,
+                        option,
+                        ast,
+                        variable_value,
+                    );
+                    Runtime::free(context, builder, module, old_ptr, value_type);
+                    variable.insert(variable_name.to_string(), value.into_pointer_value());
+                }
+                // `t = s`, copied like `var t = s` before the old string is freed
+                ExprKind::Variable(_) => {
+                    let old_ptr = *ptr;
+                    let value = compile_math_operation(
+                        context,
+                        builder,
+                        module,
+                        variable,
+                        variable_metadata,
+                        line,
+                        config,
+                        option,
+                        ast,
+                        variable_value,
+                    );
+                    let copy = Runtime::copy(
+                        context,
+                        builder,
+                        module,
+                        value.into_pointer_value(),
+                        variable_value.value_type,
+                    );
+                    Runtime::free(context, builder, module, old_ptr, value_type);
+                    variable.insert(variable_name.to_string(), copy);
+                }
+                _ => panic!(
+                    "{}",
+                    &("variable type is not valid type ".to_string() + &line.to_string())
+                ),
+            },
             VariableMetaType::Number => {
-                if let AstNodeValue::Number(num) = variable_value {
-                    if ptr.get_type().get_element_type().is_float_type() {
-                        builder.build_store(*ptr, context.f64_type().const_float(num));
-                    } else if ptr.get_type().get_element_type().is_int_type()
-                        && (num.fract() == 0.0)
-                    {
-                        // int
-                        builder.build_store(
-                            *ptr,
-                            context
-                                .i64_type()
-                                .const_int_from_string(
-                                    &num.to_string(),
-                                    inkwell::types::StringRadix::Decimal,
-                                )
-                                .unwrap(),
-                        );
-                    } else if ptr.get_type().get_element_type().is_int_type()
-                        && !(num.fract() == 0.0)
-                    {
-                        // create new variable
-                        let ptr = CompilerHelper::create_number_variable(
-                            context,
-                            builder,
-                            num,
-                            &variable_name,
-                        );
-                        variable.remove(&variable_name);
-                        variable.insert(variable_name.clone(), ptr);
-                    }
-                } else {
+                let value = compile_math_operation(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    variable_value,
+                );
+                if value.is_float_value() && ptr.get_type().get_element_type().is_int_type() {
                     panic!(
-                        "{}",
-                        &("variable type is not valid type ".to_string() + &line.to_string())
+                        "không thể gán số thực cho biến số nguyên `{}` ở dòng {}, dùng số_nguyên(...) để đổi kiểu",
+                        variable_name, line
                     )
                 }
+                // int is widened to float, a float variable can hold both
+                let value = CompilerHelper::cast_value(
+                    context,
+                    builder,
+                    module,
+                    value,
+                    variable_value.value_type,
+                    value_type,
+                );
+                builder.build_store(*ptr, value);
             }
         }
     }
 }
 
-fn compile_math_operation<'a>(
+/// `số_nguyên(x)`, `số_thực(x)`, `số_nguyên_8(x)`..., the name of a number type used as
+/// a function
+fn compile_convert_call<'a>(
     context: &'a Context,
     builder: &Builder<'a>,
+    module: &Module<'a>,
     variable: &BTreeMap<String, PointerValue<'a>>,
     variable_metadata: &BTreeMap<String, VariableMetaType>,
-    node: &AstNode,
+    line: usize,
+    config: &KeywordConfig,
+    option: &CompileOption,
+    ast: &Ast,
+    call: &Call,
 ) -> BasicValueEnum<'a> {
-    if let Operation::Value(v) = &node.op {
-        let value = v.get_math_value().unwrap();
-        match value {
-            AstNodeValue::String(_) => todo!(),
-            AstNodeValue::Number(n) => {
-                if n.fract() == 0.0 {
-                    inkwell::values::BasicValueEnum::IntValue(
-                        context
-                            .i64_type()
-                            .const_int_from_string(
-                                &n.to_string(),
-                                inkwell::types::StringRadix::Decimal,
-                            )
-                            .unwrap(),
-                    )
-                } else {
-                    inkwell::values::BasicValueEnum::FloatValue(context.f64_type().const_float(n))
-                }
+    let target = config
+        .type_keyword
+        .get(&call.function)
+        .expect(&format!("không có hàm nào tên là `{}`", call.function));
+    let value = compile_math_operation(
+        context,
+        builder,
+        module,
+        variable,
+        variable_metadata,
+        line,
+        config,
+        option,
+        ast,
+        &ast[call.args[0]],
+    );
+    CompilerHelper::cast_value(
+        context,
+        builder,
+        module,
+        value,
+        ast[call.args[0]].value_type,
+        Some(*target),
+    )
+}
+
+/// `s[i]` and `s[a..b]`, the positions count characters and are checked by the runtime
+fn compile_string_index<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    variable: &BTreeMap<String, PointerValue<'a>>,
+    variable_metadata: &BTreeMap<String, VariableMetaType>,
+    line: usize,
+    config: &KeywordConfig,
+    option: &CompileOption,
+    ast: &Ast,
+    node: &Expr,
+) -> BasicValueEnum<'a> {
+    let compile = |a: &Expr| {
+        compile_math_operation(
+            context,
+            builder,
+            module,
+            variable,
+            variable_metadata,
+            line,
+            config,
+            option,
+            ast,
+            a,
+        )
+    };
+    let position = |a: &Expr| {
+        CompilerHelper::cast_value(
+            context,
+            builder,
+            module,
+            compile(a),
+            a.value_type,
+            Some(ValueType::Int),
+        )
+        .into_int_value()
+    };
+    BasicValueEnum::PointerValue(match &node.kind {
+        ExprKind::Index { target, index } if ast[*target].value_type == Some(ValueType::List) => {
+            let list = compile(&ast[*target]).into_pointer_value();
+            Runtime::list_get(context, builder, module, list, position(&ast[*index]))
+        }
+        ExprKind::Index { target, index } => {
+            let string = compile(&ast[*target]).into_pointer_value();
+            Runtime::string_char_at(context, builder, module, string, position(&ast[*index]))
+        }
+        ExprKind::Slice { target, start, end } => {
+            let string = compile(&ast[*target]).into_pointer_value();
+            Runtime::string_slice(
+                context,
+                builder,
+                module,
+                string,
+                position(&ast[*start]),
+                end.map(|e| position(&ast[e])),
+            )
+        }
+        _ => unreachable!(),
+    })
+}
+
+/// `độ_dài(s)`, `chữ_hoa(s)`, `tách(s, ",")`... the functions of `BuiltinFunction`
+fn compile_builtin_call<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    variable: &BTreeMap<String, PointerValue<'a>>,
+    variable_metadata: &BTreeMap<String, VariableMetaType>,
+    line: usize,
+    config: &KeywordConfig,
+    option: &CompileOption,
+    ast: &Ast,
+    builtin: BuiltinFunction,
+    call: &Call,
+) -> BasicValueEnum<'a> {
+    let args = call
+        .args
+        .iter()
+        .map(|a| {
+            let a = &ast[*a];
+            let value = compile_math_operation(
+                context,
+                builder,
+                module,
+                variable,
+                variable_metadata,
+                line,
+                config,
+                option,
+                ast,
+                a,
+            );
+            // the runtime take every integer as 64 bits
+            let wide = match a.value_type.and_then(|t| t.int_info()) {
+                Some((_, true)) => Some(ValueType::Int),
+                Some((_, false)) => Some(ValueType::UInt64),
+                None => None,
+            };
+            CompilerHelper::cast_value(context, builder, module, value, a.value_type, wide).into()
+        })
+        .collect::<Vec<_>>();
+    Runtime::builtin(
+        context,
+        builder,
+        module,
+        builtin,
+        call.args.first().and_then(|a| ast[*a].value_type),
+        &args,
+    )
+}
+
+/// `làm_tròn(x, 2)`, keep 2 digits after the dot, without the digits it round to an integer
+fn compile_round_call<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    variable: &BTreeMap<String, PointerValue<'a>>,
+    variable_metadata: &BTreeMap<String, VariableMetaType>,
+    line: usize,
+    config: &KeywordConfig,
+    option: &CompileOption,
+    ast: &Ast,
+    mode: RoundingMode,
+    call: &Call,
+) -> BasicValueEnum<'a> {
+    let value = compile_math_operation(
+        context,
+        builder,
+        module,
+        variable,
+        variable_metadata,
+        line,
+        config,
+        option,
+        ast,
+        &ast[call.args[0]],
+    );
+    let digits = match call.args.get(1).map(|d| &ast[*d].kind) {
+        Some(ExprKind::Int(i)) => *i as u64,
+        Some(_) => panic!("lỗi ở dòng {}: số chữ số phải là một số nguyên", line),
+        None => 0,
+    };
+    BasicValueEnum::IntValue(Runtime::decimal_round(
+        context,
+        builder,
+        module,
+        value.into_int_value(),
+        digits,
+        mode,
+    ))
+}
+
+/// integer `+ - * / %` that stop the program with a message instead of wrapping around
+/// or crashing with SIGFPE. Two literals are computed right here, the type checker
+/// already refused the ones that divide by 0 or overflow
+fn compile_int_operation<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    line: usize,
+    option: &CompileOption,
+    operation: &BinaryOp,
+    lhs: IntValue<'a>,
+    rhs: IntValue<'a>,
+    value_type: ValueType,
+) -> IntValue<'a> {
+    let (bits, signed) = value_type.int_info().unwrap_or((64, true));
+    let int_type = lhs.get_type();
+    let overflow_message = format!(
+        "lỗi ở dòng {}: phép tính bị tràn số, kết quả vượt quá giới hạn của kiểu {}",
+        line, value_type
+    );
+    let divide_by_zero_message = format!("lỗi ở dòng {}: không thể chia cho 0", line);
+
+    if lhs.is_const() && rhs.is_const() {
+        let read = |v: IntValue<'a>| -> i128 {
+            if signed {
+                v.get_sign_extended_constant().unwrap() as i128
+            } else {
+                v.get_zero_extended_constant().unwrap() as i128
             }
-            AstNodeValue::None => todo!(),
-            AstNodeValue::Bool(_) => todo!(),
-            AstNodeValue::Variable(name) => {
-                let var_ptr = variable
-                    .get(&name)
-                    .expect("sao dùng biến được khi không có biến dậy anh zai anh zai dùng kiểu gì")
-                    .clone();
-                builder.build_load(var_ptr, "load")
+        };
+        let (a, b) = (read(lhs), read(rhs));
+        let result = match operation {
+            BinaryOp::Plus => Some(a + b),
+            BinaryOp::Minus => Some(a - b),
+            BinaryOp::Times => a.checked_mul(b),
+            BinaryOp::Divide | BinaryOp::Remainder if b == 0 => None,
+            BinaryOp::Divide => Some(a / b),
+            BinaryOp::Remainder => Some(a % b),
+            _ => unreachable!("so sánh được dịch trong compile_comparison"),
+        };
+        let (min, max) = value_type
+            .int_range()
+            .unwrap_or((i64::MIN as i128, i64::MAX as i128));
+        let wraps = option.wrapping_arithmetic
+            && !matches!(operation, BinaryOp::Divide | BinaryOp::Remainder);
+        match result {
+            Some(result) if wraps || (min..=max).contains(&result) => {
+                return int_type.const_int(result as u64, signed);
             }
-            AstNodeValue::Operation(_) => todo!(),
+            // a constant the type checker could not see, like `số_nguyên_8(200) + 100`,
+            // is checked when the program run
+            _ => {}
         }
-    } else if let Operation::IntOperation(i) = &node.op {
-        let fn_match_op = |a: &AstNode| match &a.op {
-            Operation::None => todo!(),
-            Operation::Ident(_) => todo!(),
-            Operation::Value(_) => {
-                compile_math_operation(context, builder, variable, variable_metadata, a)
-            }
-            Operation::Call => todo!(),
-            Operation::NewVariable => todo!(),
-            Operation::SetVariable => todo!(),
-            Operation::IntOperation(_) => {
-                compile_math_operation(context, builder, variable, variable_metadata, a)
+    }
+
+    match operation {
+        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Times if option.wrapping_arithmetic => {
+            match operation {
+                BinaryOp::Plus => builder.build_int_add(lhs, rhs, ""),
+                BinaryOp::Minus => builder.build_int_sub(lhs, rhs, ""),
+                _ => builder.build_int_mul(lhs, rhs, ""),
             }
-        };
-        let left_value = fn_match_op(&node.left[0]);
-        let right_value = fn_match_op(&node.right[0]);
-        if left_value.is_float_value() && right_value.is_int_value() {
-            let lhs = inkwell::values::FloatValue::from(left_value.into_float_value());
-            let rhs = builder.build_signed_int_to_float(
-                right_value.into_int_value(),
-                context.f64_type(),
-                "",
+        }
+        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Times => {
+            let intrinsic_name = format!(
+                "llvm.{}{}.with.overflow.i{}",
+                if signed { "s" } else { "u" },
+                match operation {
+                    BinaryOp::Plus => "add",
+                    BinaryOp::Minus => "sub",
+                    _ => "mul",
+                },
+                bits
             );
-            match i {
-                crate::parse::IntOperationType::Plus => {
-                    return inkwell::values::BasicValueEnum::FloatValue(
-                        builder.build_float_add(lhs, rhs, ""),
-                    )
-                }
-                crate::parse::IntOperationType::Minus => {
-                    return inkwell::values::BasicValueEnum::FloatValue(
-                        builder.build_float_sub(lhs, rhs, ""),
-                    )
-                }
-                crate::parse::IntOperationType::Times => {
-                    return inkwell::values::BasicValueEnum::FloatValue(
-                        builder.build_float_mul(lhs, rhs, ""),
+            let intrinsic = match module.get_function(&intrinsic_name) {
+                Some(f) => f,
+                None => {
+                    let return_type =
+                        context.struct_type(&[int_type.into(), context.bool_type().into()], false);
+                    module.add_function(
+                        &intrinsic_name,
+                        return_type.fn_type(&[int_type.into(), int_type.into()], false),
+                        None,
                     )
                 }
-                crate::parse::IntOperationType::Divide => {
-                    return inkwell::values::BasicValueEnum::FloatValue(
-                        builder.build_float_div(lhs, rhs, ""),
-                    )
-                }
-                crate::parse::IntOperationType::None => todo!(),
+            };
+            let result = builder
+                .build_call(intrinsic, &[lhs.into(), rhs.into()], "checked")
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+                .into_struct_value();
+            let overflow = builder
+                .build_extract_value(result, 1, "overflow")
+                .unwrap()
+                .into_int_value();
+            CompilerHelper::build_runtime_check(
+                context,
+                builder,
+                module,
+                overflow,
+                &overflow_message,
+            );
+            builder
+                .build_extract_value(result, 0, "value")
+                .unwrap()
+                .into_int_value()
+        }
+        BinaryOp::Divide | BinaryOp::Remainder => {
+            let is_zero =
+                builder.build_int_compare(IntPredicate::EQ, rhs, int_type.const_zero(), "is_zero");
+            CompilerHelper::build_runtime_check(
+                context,
+                builder,
+                module,
+                is_zero,
+                &divide_by_zero_message,
+            );
+            if signed && matches!(operation, BinaryOp::Remainder) {
+                // `x % -1` is always 0 but x86 raise SIGFPE for the smallest value, `x % 1`
+                // give the same result without the trap
+                let is_minus_one = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    rhs,
+                    int_type.const_int(u64::MAX, true),
+                    "is_minus_one",
+                );
+                let rhs = builder
+                    .build_select(is_minus_one, int_type.const_int(1, false), rhs, "rhs")
+                    .into_int_value();
+                builder.build_int_signed_rem(lhs, rhs, "")
+            } else if matches!(operation, BinaryOp::Remainder) {
+                builder.build_int_unsigned_rem(lhs, rhs, "")
+            } else if signed {
+                // the smallest value divided by -1 does not fit, x86 raise SIGFPE for it
+                let is_min = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    lhs,
+                    int_type.const_int(1 << (bits - 1), false),
+                    "is_min",
+                );
+                let is_minus_one = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    rhs,
+                    int_type.const_int(u64::MAX, true),
+                    "is_minus_one",
+                );
+                let overflow = builder.build_and(is_min, is_minus_one, "overflow");
+                CompilerHelper::build_runtime_check(
+                    context,
+                    builder,
+                    module,
+                    overflow,
+                    &overflow_message,
+                );
+                builder.build_int_signed_div(lhs, rhs, "")
+            } else {
+                builder.build_int_unsigned_div(lhs, rhs, "")
             }
-        } else if left_value.is_float_value() && right_value.is_float_value() {
-            let lhs = left_value.into_float_value();
-            let rhs = right_value.into_float_value();
-            match i {
-                crate::parse::IntOperationType::Plus => {
-                    BasicValueEnum::FloatValue(builder.build_float_add(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Minus => {
-                    BasicValueEnum::FloatValue(builder.build_float_sub(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Times => {
-                    BasicValueEnum::FloatValue(builder.build_float_mul(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Divide => {
-                    BasicValueEnum::FloatValue(builder.build_float_div(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::None => todo!(),
+        }
+        _ => unreachable!("so sánh được dịch trong compile_comparison"),
+    }
+}
+
+fn compile_math_operation<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    variable: &BTreeMap<String, PointerValue<'a>>,
+    variable_metadata: &BTreeMap<String, VariableMetaType>,
+    line: usize,
+    config: &KeywordConfig,
+    option: &CompileOption,
+    ast: &Ast,
+    node: &Expr,
+) -> BasicValueEnum<'a> {
+    match &node.kind {
+        ExprKind::Float(f) if node.value_type == Some(ValueType::Decimal) => {
+            // a float literal the type checker turned into a decimal, read from its text
+            // so `0.1` is exact
+            let decimal = parse_decimal_literal(&f.to_string()).unwrap();
+            BasicValueEnum::IntValue(context.i64_type().const_int(decimal as u64, true))
+        }
+        ExprKind::Int(_) => {
+            // the type checker may have given the literal a sized integer type
+            CompilerHelper::cast_value(
+                context,
+                builder,
+                module,
+                CompilerHelper::const_number(context, &node.kind),
+                Some(ValueType::Int),
+                node.value_type,
+            )
+        }
+        ExprKind::Float(_) | ExprKind::Decimal(_) => {
+            CompilerHelper::const_number(context, &node.kind)
+        }
+        ExprKind::String(s) => BasicValueEnum::PointerValue(
+            CompilerHelper::create_constant_string(builder, s.clone(), "str"),
+        ),
+        ExprKind::Bool(b) => {
+            BasicValueEnum::IntValue(context.bool_type().const_int(*b as u64, false))
+        }
+        ExprKind::Variable(name) => {
+            let var_ptr = variable
+                .get(name)
+                .expect("sao dùng biến được khi không có biến dậy anh zai anh zai dùng kiểu gì")
+                .clone();
+            match variable_metadata.get(name) {
+                // the variable of a string is the string itself, not a slot holding it
+                Some(VariableMetaType::String) => BasicValueEnum::PointerValue(var_ptr),
+                _ => builder.build_load(var_ptr, "load"),
             }
-        } else if left_value.is_int_value() && right_value.is_int_value() {
-            let lhs = left_value.into_int_value();
-            let rhs = right_value.into_int_value();
-            match i {
-                crate::parse::IntOperationType::Plus => {
-                    BasicValueEnum::IntValue(builder.build_int_add(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Minus => {
-                    BasicValueEnum::IntValue(builder.build_int_sub(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Times => {
-                    BasicValueEnum::IntValue(builder.build_int_mul(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Divide => {
-                    BasicValueEnum::IntValue(builder.build_int_signed_div(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::None => todo!(),
+        }
+        ExprKind::Call(call) => {
+            let function_type = config
+                .default_function
+                .get(&call.function)
+                .map(|f| f.r#type.clone())
+                .unwrap_or_default();
+            if let Some(builtin) = BuiltinFunction::from_config_type(&function_type) {
+                return compile_builtin_call(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    builtin,
+                    call,
+                );
             }
-        } else if left_value.is_int_value() && right_value.is_float_value() {
-            let lhs = builder.build_signed_int_to_float(
-                left_value.into_int_value(),
-                context.f64_type(),
-                "",
-            );
-            let rhs = inkwell::values::FloatValue::from(right_value.into_float_value());
-
-            match i {
-                crate::parse::IntOperationType::Plus => {
-                    BasicValueEnum::FloatValue(builder.build_float_add(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Minus => {
-                    BasicValueEnum::FloatValue(builder.build_float_sub(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Times => {
-                    BasicValueEnum::FloatValue(builder.build_float_mul(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::Divide => {
-                    BasicValueEnum::FloatValue(builder.build_float_div(lhs, rhs, ""))
-                }
-                crate::parse::IntOperationType::None => todo!(),
+            if let Some(mode) = RoundingMode::from_config_type(&function_type) {
+                return compile_round_call(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    mode,
+                    call,
+                );
             }
-        } else {
-            todo!()
+            compile_convert_call(
+                context,
+                builder,
+                module,
+                variable,
+                variable_metadata,
+                line,
+                config,
+                option,
+                ast,
+                call,
+            )
         }
+        ExprKind::Index { .. } | ExprKind::Slice { .. } => compile_string_index(
+            context,
+            builder,
+            module,
+            variable,
+            variable_metadata,
+            line,
+            config,
+            option,
+            ast,
+            node,
+        ),
+        ExprKind::Binary { op, left, right } => {
+            let compile = |a: &Expr| {
+                compile_math_operation(
+                    context,
+                    builder,
+                    module,
+                    variable,
+                    variable_metadata,
+                    line,
+                    config,
+                    option,
+                    ast,
+                    a,
+                )
+            };
+            let (left, right) = (&ast[*left], &ast[*right]);
+            let left_value = compile(left);
+            let right_value = compile(right);
+            compile_binary_operation(
+                context,
+                builder,
+                module,
+                line,
+                option,
+                op,
+                (left_value, left.value_type),
+                (right_value, right.value_type),
+                node.value_type,
+            )
+        }
+    }
+}
+
+/// `+ - * / %` and the comparisons between two values already compiled, an int on one
+/// side is promoted to the type of the other one
+fn compile_binary_operation<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    line: usize,
+    option: &CompileOption,
+    operation: &BinaryOp,
+    (left_value, left_type): (BasicValueEnum<'a>, Option<ValueType>),
+    (right_value, right_type): (BasicValueEnum<'a>, Option<ValueType>),
+    value_type: Option<ValueType>,
+) -> BasicValueEnum<'a> {
+    if operation.is_comparison() {
+        BasicValueEnum::IntValue(compile_comparison(
+            context,
+            builder,
+            module,
+            operation,
+            (left_value, left_type),
+            (right_value, right_type),
+        ))
+    } else if value_type == Some(ValueType::Decimal) {
+        let lhs = CompilerHelper::cast_value(
+            context,
+            builder,
+            module,
+            left_value,
+            left_type,
+            Some(ValueType::Decimal),
+        )
+        .into_int_value();
+        let rhs = CompilerHelper::cast_value(
+            context,
+            builder,
+            module,
+            right_value,
+            right_type,
+            Some(ValueType::Decimal),
+        )
+        .into_int_value();
+        BasicValueEnum::IntValue(match operation {
+            // same scale on both side, a plain i64 addition or remainder is exact
+            BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Remainder => compile_int_operation(
+                context,
+                builder,
+                module,
+                line,
+                option,
+                operation,
+                lhs,
+                rhs,
+                ValueType::Decimal,
+            ),
+            _ => Runtime::decimal_mul_div(
+                context,
+                builder,
+                module,
+                matches!(operation, BinaryOp::Divide),
+                lhs,
+                rhs,
+            ),
+        })
+    } else if left_value.is_int_value() && right_value.is_int_value() {
+        BasicValueEnum::IntValue(compile_int_operation(
+            context,
+            builder,
+            module,
+            line,
+            option,
+            operation,
+            left_value.into_int_value(),
+            right_value.into_int_value(),
+            value_type.unwrap_or(ValueType::Int),
+        ))
+    } else if left_value.is_float_value() || right_value.is_float_value() {
+        // an int on one side is promoted to float
+        let lhs = CompilerHelper::cast_value(
+            context,
+            builder,
+            module,
+            left_value,
+            left_type,
+            Some(ValueType::Float),
+        )
+        .into_float_value();
+        let rhs = CompilerHelper::cast_value(
+            context,
+            builder,
+            module,
+            right_value,
+            right_type,
+            Some(ValueType::Float),
+        )
+        .into_float_value();
+        BasicValueEnum::FloatValue(match operation {
+            BinaryOp::Plus => builder.build_float_add(lhs, rhs, ""),
+            BinaryOp::Minus => builder.build_float_sub(lhs, rhs, ""),
+            BinaryOp::Times => builder.build_float_mul(lhs, rhs, ""),
+            BinaryOp::Divide => builder.build_float_div(lhs, rhs, ""),
+            _ => builder.build_float_rem(lhs, rhs, ""),
+        })
     } else {
-        todo!()
+        unreachable!("phép tính chỉ dùng số, bị kiểm tra kiểu từ chối")
     }
 }
+
+/// `a lớn hơn b`, the two sides are compared as decimals, floats or integers like the
+/// operation `a + b` would be computed
+fn compile_comparison<'a>(
+    context: &'a Context,
+    builder: &Builder<'a>,
+    module: &Module<'a>,
+    operation: &BinaryOp,
+    (left_value, left_type): (BasicValueEnum<'a>, Option<ValueType>),
+    (right_value, right_type): (BasicValueEnum<'a>, Option<ValueType>),
+) -> IntValue<'a> {
+    let decimal = left_type == Some(ValueType::Decimal) || right_type == Some(ValueType::Decimal);
+    if !decimal && (left_value.is_float_value() || right_value.is_float_value()) {
+        let float = |value, value_type| {
+            CompilerHelper::cast_value(
+                context,
+                builder,
+                module,
+                value,
+                value_type,
+                Some(ValueType::Float),
+            )
+            .into_float_value()
+        };
+        // `khác` is true when one side is NaN, every other comparison is false
+        let predicate = match operation {
+            BinaryOp::Less => FloatPredicate::OLT,
+            BinaryOp::LessEqual => FloatPredicate::OLE,
+            BinaryOp::Greater => FloatPredicate::OGT,
+            BinaryOp::GreaterEqual => FloatPredicate::OGE,
+            BinaryOp::Equal => FloatPredicate::OEQ,
+            _ => FloatPredicate::UNE,
+        };
+        return builder.build_float_compare(
+            predicate,
+            float(left_value, left_type),
+            float(right_value, right_type),
+            "compare",
+        );
+    }
+    let (lhs, rhs) = if decimal {
+        let to_decimal = |value, value_type| {
+            CompilerHelper::cast_value(
+                context,
+                builder,
+                module,
+                value,
+                value_type,
+                Some(ValueType::Decimal),
+            )
+            .into_int_value()
+        };
+        (
+            to_decimal(left_value, left_type),
+            to_decimal(right_value, right_type),
+        )
+    } else {
+        (left_value.into_int_value(), right_value.into_int_value())
+    };
+    // the type checker gave both sides the same integer type
+    let signed = decimal || left_type.and_then(|t| t.int_info()).is_none_or(|(_, s)| s);
+    let predicate = match (operation, signed) {
+        (BinaryOp::Less, true) => IntPredicate::SLT,
+        (BinaryOp::Less, false) => IntPredicate::ULT,
+        (BinaryOp::LessEqual, true) => IntPredicate::SLE,
+        (BinaryOp::LessEqual, false) => IntPredicate::ULE,
+        (BinaryOp::Greater, true) => IntPredicate::SGT,
+        (BinaryOp::Greater, false) => IntPredicate::UGT,
+        (BinaryOp::GreaterEqual, true) => IntPredicate::SGE,
+        (BinaryOp::GreaterEqual, false) => IntPredicate::UGE,
+        (BinaryOp::Equal, _) => IntPredicate::EQ,
+        _ => IntPredicate::NE,
+    };
+    builder.build_int_compare(predicate, lhs, rhs, "compare")
+}
@@ -1,4 +1,5 @@
 mod helper;
+mod runtime;
 use std::{
     collections::BTreeMap,
     path::PathBuf,
@@ -15,15 +16,30 @@ use inkwell::{
     OptimizationLevel,
 };
 
-use crate::parse::{Ast, AstNode, KeywordConfig};
+use crate::parse::{walk_ast, walk_stmt, Ast, ExprKind, KeywordConfig, Stmt, StmtId, Visitor};
 
-use self::helper::ParseExpr;
+use self::{helper::ParseExpr, runtime::Runtime};
 
+#[derive(Clone)]
 pub enum VariableMetaType {
     String,
     Number,
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CompileOption {
+    /// integer `+ - *` wrap around on overflow instead of stopping the program,
+    /// faster but the result is wrong silently
+    pub wrapping_arithmetic: bool,
+}
+
+/// a variable declared in a block, with what its name meant before
+struct Declared<'a> {
+    declaration: &'a Stmt,
+    hidden: Option<PointerValue<'a>>,
+    hidden_metadata: Option<VariableMetaType>,
+}
+
 pub struct Compiler<'a> {
     context: &'a Context,
     module: Module<'a>,
@@ -32,8 +48,10 @@ pub struct Compiler<'a> {
     config: &'a KeywordConfig,
     variable: Arc<Mutex<BTreeMap<String, PointerValue<'a>>>>,
     variable_metadata: Arc<Mutex<BTreeMap<String, VariableMetaType>>>,
-    function_name_dist: Arc<Mutex<BTreeMap<String, String>>>,
+    option: CompileOption,
     line: usize,
+    /// the declarations of each open block, undone at its end
+    blocks: Vec<Vec<Declared<'a>>>,
 }
 
 impl<'a> Compiler<'a> {
@@ -43,6 +61,7 @@ impl<'a> Compiler<'a> {
         builder: Builder<'a>,
         ast: &'a Ast,
         config: &'a KeywordConfig,
+        option: CompileOption,
     ) -> Self {
         Self {
             context,
@@ -53,7 +72,8 @@ impl<'a> Compiler<'a> {
             variable: Default::default(),
             line: 0,
             variable_metadata: Default::default(),
-            function_name_dist: Default::default(),
+            option,
+            blocks: vec![],
         }
     }
     pub fn parse_ast_to_module(&mut self) {
@@ -67,34 +87,101 @@ impl<'a> Compiler<'a> {
         let basic_block = self.context.append_basic_block(main_fn, "entry");
         self.builder.position_at_end(basic_block);
 
-        for c in self.ast {
-            self.line = self.line + 1;
-            self.parse_command(c, &self.builder);
-        }
+        let ast = self.ast;
+        walk_ast(self, ast);
 
         let i32_zero = i32_type.const_int(0, false);
         self.builder.build_return(Some(&i32_zero));
     }
 
-    fn parse_command(&self, command: &AstNode, builder: &Builder<'a>) {
-        match command.op {
-            crate::parse::Operation::None => todo!(),
-            crate::parse::Operation::Ident(_) => todo!(),
-            crate::parse::Operation::Value(_) => todo!(),
-            crate::parse::Operation::Call => {
+    /// the end of the block of `declaration`: the string or list of the variable is freed
+    /// and its name get back what it meant before. A variable of outside given a new value
+    /// in the block keep it, only the names declared inside are dropped
+    fn drop_variable(&self, declared: Declared<'a>) {
+        let Declared {
+            declaration,
+            hidden,
+            hidden_metadata,
+        } = declared;
+        let Stmt::Let {
+            name,
+            constant,
+            value,
+            value_type,
+            ..
+        } = declaration
+        else {
+            unreachable!("chỉ khai báo được thêm vào khối")
+        };
+        let mut variable = self.variable.lock().unwrap();
+        let mut variable_metadata = self.variable_metadata.lock().unwrap();
+        // a constant string literal is a global of the program, every other string is owned
+        let is_global =
+            *constant && matches!(value.map(|v| &self.ast[v].kind), Some(ExprKind::String(_)));
+        if let (Some(VariableMetaType::String), false) = (variable_metadata.get(name), is_global) {
+            Runtime::free(
+                self.context,
+                &self.builder,
+                &self.module,
+                variable[name],
+                *value_type,
+            );
+        }
+        match (hidden, hidden_metadata) {
+            (Some(ptr), Some(metadata)) => {
+                variable.insert(name.clone(), ptr);
+                variable_metadata.insert(name.clone(), metadata);
+            }
+            _ => {
+                variable.remove(name);
+                variable_metadata.remove(name);
+            }
+        }
+    }
+
+    fn parse_command(&self, command: &Stmt, builder: &Builder<'a>) {
+        match command {
+            Stmt::Call { call, .. } => {
                 ParseExpr::parse_call_function_syntax(
                     self.context,
                     &builder,
                     &self.module,
                     &Arc::clone(&self.variable).lock().unwrap(),
                     &Arc::clone(&self.variable_metadata).lock().unwrap(),
-                    &self.function_name_dist.lock().unwrap(),
                     self.line,
                     self.config,
-                    command,
+                    &self.option,
+                    self.ast,
+                    call,
                 );
             }
-            crate::parse::Operation::NewVariable => ParseExpr::parse_new_variable_syntax(
+            Stmt::Let {
+                name,
+                constant: false,
+                value,
+                value_type,
+                ..
+            } => ParseExpr::parse_new_variable_syntax(
+                self.context,
+                &builder,
+                &self.module,
+                &mut Arc::clone(&self.variable).lock().unwrap(),
+                &mut Arc::clone(&self.variable_metadata).lock().unwrap(),
+                self.line,
+                self.config,
+                &self.option,
+                self.ast,
+                name,
+                value.map(|v| &self.ast[v]),
+                *value_type,
+            ),
+            Stmt::Let {
+                name,
+                constant: true,
+                value,
+                value_type,
+                ..
+            } => ParseExpr::parse_new_constant_syntax(
                 self.context,
                 &builder,
                 &self.module,
@@ -102,9 +189,18 @@ impl<'a> Compiler<'a> {
                 &mut Arc::clone(&self.variable_metadata).lock().unwrap(),
                 self.line,
                 self.config,
-                command,
+                &self.option,
+                self.ast,
+                name,
+                &self.ast[value.unwrap()],
+                *value_type,
             ),
-            crate::parse::Operation::SetVariable => ParseExpr::parse_set_variable_syntax(
+            Stmt::Assign {
+                name,
+                value,
+                value_type,
+                ..
+            } => ParseExpr::parse_set_variable_syntax(
                 self.context,
                 &builder,
                 &self.module,
@@ -112,9 +208,15 @@ impl<'a> Compiler<'a> {
                 &mut Arc::clone(&self.variable_metadata).lock().unwrap(),
                 self.line,
                 self.config,
-                command,
+                &self.option,
+                self.ast,
+                name,
+                &self.ast[*value],
+                *value_type,
             ),
-            crate::parse::Operation::IntOperation(_) => todo!(),
+            Stmt::Block { .. } => {
+                unreachable!("khối được dịch trong visit_stmt")
+            }
         }
     }
 
@@ -153,6 +255,33 @@ impl<'a> Compiler<'a> {
     }
 }
 
+impl<'a> Visitor<'a> for Compiler<'a> {
+    fn visit_stmt(&mut self, ast: &'a Ast, id: StmtId) {
+        let statement = &ast[id];
+        self.line = statement.span().line;
+        match statement {
+            Stmt::Block { .. } => {
+                self.blocks.push(vec![]);
+                walk_stmt(self, ast, id);
+                for declared in self.blocks.pop().unwrap().into_iter().rev() {
+                    self.drop_variable(declared);
+                }
+            }
+            Stmt::Let { name, .. } if !self.blocks.is_empty() => {
+                let hidden = self.variable.lock().unwrap().get(name).copied();
+                let hidden_metadata = self.variable_metadata.lock().unwrap().get(name).cloned();
+                self.parse_command(statement, &self.builder);
+                self.blocks.last_mut().unwrap().push(Declared {
+                    declaration: statement,
+                    hidden,
+                    hidden_metadata,
+                });
+            }
+            _ => self.parse_command(statement, &self.builder),
+        }
+    }
+}
+
 fn pre_save_file(path: &str) {
     let path = PathBuf::from_str(path).unwrap();
     std::fs::create_dir_all(path.parent().unwrap()).unwrap();
@@ -0,0 +1,350 @@
+use inkwell::{
+    builder::Builder,
+    context::Context,
+    module::Module,
+    types::FunctionType,
+    values::{
+        BasicMetadataValueEnum, BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue,
+    },
+};
+
+use crate::parse::{BuiltinFunction, RoundingMode, ValueType};
+
+/// functions of the runtime library in `runtime/`, the object file written by the
+/// compiler must be linked with `libvipl_runtime.a` when one of them is used
+pub struct Runtime;
+
+/// must be the same as `DECIMAL_FORMAT_BUFFER_SIZE` in the runtime
+static DECIMAL_FORMAT_BUFFER_SIZE: u32 = 32;
+/// must be the same as `SLICE_TO_END` in the runtime
+static SLICE_TO_END: i64 = i64::MAX;
+
+impl Runtime {
+    fn get_function<'a>(
+        module: &Module<'a>,
+        name: &str,
+        fn_type: FunctionType<'a>,
+    ) -> FunctionValue<'a> {
+        match module.get_function(name) {
+            Some(f) => f,
+            None => module.add_function(name, fn_type, Some(inkwell::module::Linkage::External)),
+        }
+    }
+    fn call<'a>(
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        name: &str,
+        fn_type: FunctionType<'a>,
+        args: &[BasicMetadataValueEnum<'a>],
+    ) -> BasicValueEnum<'a> {
+        let function = Self::get_function(module, name, fn_type);
+        builder
+            .build_call(function, args, name)
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+    }
+
+    pub fn decimal_from_int<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+        signed: bool,
+    ) -> IntValue<'a> {
+        let i64_type = context.i64_type();
+        Self::call(
+            builder,
+            module,
+            if signed {
+                "vipl_decimal_from_int"
+            } else {
+                "vipl_decimal_from_uint"
+            },
+            i64_type.fn_type(&[i64_type.into()], false),
+            &[value.into()],
+        )
+        .into_int_value()
+    }
+    pub fn decimal_from_float<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: FloatValue<'a>,
+    ) -> IntValue<'a> {
+        Self::call(
+            builder,
+            module,
+            "vipl_decimal_from_float",
+            context
+                .i64_type()
+                .fn_type(&[context.f64_type().into()], false),
+            &[value.into()],
+        )
+        .into_int_value()
+    }
+    pub fn decimal_to_float<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+    ) -> FloatValue<'a> {
+        Self::call(
+            builder,
+            module,
+            "vipl_decimal_to_float",
+            context
+                .f64_type()
+                .fn_type(&[context.i64_type().into()], false),
+            &[value.into()],
+        )
+        .into_float_value()
+    }
+    pub fn decimal_to_int<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+    ) -> IntValue<'a> {
+        let i64_type = context.i64_type();
+        Self::call(
+            builder,
+            module,
+            "vipl_decimal_to_int",
+            i64_type.fn_type(&[i64_type.into()], false),
+            &[value.into()],
+        )
+        .into_int_value()
+    }
+    /// `*` and `/` of two decimals, `+` and `-` are plain checked i64 operations
+    pub fn decimal_mul_div<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        is_divide: bool,
+        lhs: IntValue<'a>,
+        rhs: IntValue<'a>,
+    ) -> IntValue<'a> {
+        let i64_type = context.i64_type();
+        Self::call(
+            builder,
+            module,
+            if is_divide {
+                "vipl_decimal_div"
+            } else {
+                "vipl_decimal_mul"
+            },
+            i64_type.fn_type(&[i64_type.into(), i64_type.into()], false),
+            &[lhs.into(), rhs.into()],
+        )
+        .into_int_value()
+    }
+    pub fn decimal_round<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+        digits: u64,
+        mode: RoundingMode,
+    ) -> IntValue<'a> {
+        let i64_type = context.i64_type();
+        let i32_type = context.i32_type();
+        Self::call(
+            builder,
+            module,
+            "vipl_decimal_round",
+            i64_type.fn_type(&[i64_type.into(), i32_type.into(), i32_type.into()], false),
+            &[
+                value.into(),
+                i32_type.const_int(digits, false).into(),
+                i32_type.const_int(mode as u64, false).into(),
+            ],
+        )
+        .into_int_value()
+    }
+    /// a new copy of a string or a list, for a binding made from another variable
+    pub fn copy<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: PointerValue<'a>,
+        value_type: Option<ValueType>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        Self::call(
+            builder,
+            module,
+            if value_type == Some(ValueType::List) {
+                "vipl_list_copy"
+            } else {
+                "vipl_string_copy"
+            },
+            i8_ptr_type.fn_type(&[i8_ptr_type.into()], false),
+            &[value.into()],
+        )
+        .into_pointer_value()
+    }
+    /// free a string or a list of the program, the strings of a list are freed with it
+    pub fn free<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: PointerValue<'a>,
+        value_type: Option<ValueType>,
+    ) {
+        if value_type == Some(ValueType::List) {
+            let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+            let function = Self::get_function(
+                module,
+                "vipl_list_free",
+                context.void_type().fn_type(&[i8_ptr_type.into()], false),
+            );
+            builder.build_call(function, &[value.into()], "");
+        } else {
+            builder.build_free(value);
+        }
+    }
+    /// `s[index]`, a new string holding one character
+    pub fn string_char_at<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        string: PointerValue<'a>,
+        index: IntValue<'a>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        Self::call(
+            builder,
+            module,
+            "vipl_string_char_at",
+            i8_ptr_type.fn_type(&[i8_ptr_type.into(), context.i64_type().into()], false),
+            &[string.into(), index.into()],
+        )
+        .into_pointer_value()
+    }
+    /// `s[start..end]`, without `end` the slice go to the end of the string
+    pub fn string_slice<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        string: PointerValue<'a>,
+        start: IntValue<'a>,
+        end: Option<IntValue<'a>>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        let i64_type = context.i64_type();
+        let end = end.unwrap_or_else(|| i64_type.const_int(SLICE_TO_END as u64, true));
+        Self::call(
+            builder,
+            module,
+            "vipl_string_slice",
+            i8_ptr_type.fn_type(
+                &[i8_ptr_type.into(), i64_type.into(), i64_type.into()],
+                false,
+            ),
+            &[string.into(), start.into(), end.into()],
+        )
+        .into_pointer_value()
+    }
+    /// call a function of the standard library, a string or a list is passed as a
+    /// pointer and an integer as i64
+    pub fn builtin<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        function: BuiltinFunction,
+        first_argument: Option<ValueType>,
+        args: &[BasicMetadataValueEnum<'a>],
+    ) -> BasicValueEnum<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        let parameters = args
+            .iter()
+            .map(|a| match a {
+                BasicMetadataValueEnum::IntValue(_) => context.i64_type().into(),
+                BasicMetadataValueEnum::FloatValue(_) => context.f64_type().into(),
+                _ => i8_ptr_type.into(),
+            })
+            .collect::<Vec<_>>();
+        let fn_type = match function.return_type() {
+            ValueType::Bool => context.i32_type().fn_type(&parameters, false),
+            t if t.is_int() => context.i64_type().fn_type(&parameters, false),
+            _ => i8_ptr_type.fn_type(&parameters, false),
+        };
+        let result = Self::call(
+            builder,
+            module,
+            function.runtime_name(first_argument),
+            fn_type,
+            args,
+        );
+        match function.return_type() {
+            // the runtime return a C int, 0 is false
+            ValueType::Bool => BasicValueEnum::IntValue(builder.build_int_compare(
+                inkwell::IntPredicate::NE,
+                result.into_int_value(),
+                context.i32_type().const_zero(),
+                "to_bool",
+            )),
+            _ => result,
+        }
+    }
+    /// `ds[index]`, a copy of the string at `index`
+    pub fn list_get<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        list: PointerValue<'a>,
+        index: IntValue<'a>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        Self::call(
+            builder,
+            module,
+            "vipl_list_get",
+            i8_ptr_type.fn_type(&[i8_ptr_type.into(), context.i64_type().into()], false),
+            &[list.into(), index.into()],
+        )
+        .into_pointer_value()
+    }
+    /// the text of a list for printf `%s`, a new string
+    pub fn list_format<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        list: PointerValue<'a>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        Self::call(
+            builder,
+            module,
+            "vipl_list_format",
+            i8_ptr_type.fn_type(&[i8_ptr_type.into()], false),
+            &[list.into()],
+        )
+        .into_pointer_value()
+    }
+    /// the text of a decimal for printf `%s`, written to a buffer on the stack
+    pub fn decimal_format<'a>(
+        context: &'a Context,
+        builder: &Builder<'a>,
+        module: &Module<'a>,
+        value: IntValue<'a>,
+    ) -> PointerValue<'a> {
+        let i8_ptr_type = context.i8_type().ptr_type(inkwell::AddressSpace::Generic);
+        let buffer = builder.build_array_alloca(
+            context.i8_type(),
+            context
+                .i32_type()
+                .const_int(DECIMAL_FORMAT_BUFFER_SIZE as u64, false),
+            "decimal_text",
+        );
+        Self::call(
+            builder,
+            module,
+            "vipl_decimal_format",
+            i8_ptr_type.fn_type(&[context.i64_type().into(), i8_ptr_type.into()], false),
+            &[value.into(), buffer.into()],
+        )
+        .into_pointer_value()
+    }
+}
@@ -0,0 +1,9 @@
+//! the compiler of VIPL as a library, so our own tools can read a program and walk it
+//! with `parse::Visitor` without going through the `compiler` binary
+
+pub mod compiler;
+pub mod parse;
+pub mod resolve;
+pub mod source;
+pub mod type_check;
+mod util;
@@ -1,22 +1,119 @@
-mod compiler;
-mod parse;
-mod util;
+use vipl::{compiler, parse, resolve, source, type_check};
+
+/// the keyword packs are `packs/<name>.toml` in this directory
+const CONFIG_DIRECTORY: &str = "/home/tritranduc/dev/code/vietnamese-program-language/config";
+const DEFAULT_PACK: &str = "vi";
+
+fn load_pack(name: &str) -> parse::KeywordConfig {
+    let path = std::path::Path::new(CONFIG_DIRECTORY)
+        .join("packs")
+        .join(format!("{}.toml", name));
+    parse::load_config(&path).unwrap_or_else(|errors| {
+        for e in &errors {
+            eprintln!("{}", e);
+        }
+        std::process::exit(1)
+    })
+}
+
+/// `--encoding=tcvn3` when the guess is wrong, see `source::Encoding::from_name`
+fn read_source(path: &str, flags: &[String]) -> String {
+    let encoding = flags
+        .iter()
+        .find_map(|f| f.strip_prefix("--encoding="))
+        .map(|name| {
+            source::Encoding::from_name(name).unwrap_or_else(|| {
+                eprintln!("lỗi: không biết bảng mã `{}`", name);
+                std::process::exit(1)
+            })
+        });
+    source::load_source(&std::fs::read(path).unwrap(), encoding).unwrap_or_else(|e| {
+        eprintln!("lỗi: {}", e);
+        std::process::exit(1)
+    })
+}
+
+/// `vipl translate --to en bai_tap.vipl`, the program is printed with the keywords of the
+/// other pack
+fn translate(args: &[String]) {
+    let mut target = None;
+    let mut path = None;
+    let mut flags = vec![];
+    let mut rest = args.iter();
+    while let Some(a) = rest.next() {
+        if a == "--to" {
+            target = rest.next().cloned();
+        } else if let Some(pack) = a.strip_prefix("--to=") {
+            target = Some(pack.to_string());
+        } else if a.starts_with("--") {
+            flags.push(a.clone());
+        } else {
+            path = Some(a.clone());
+        }
+    }
+    let (target, path) = match (target, path) {
+        (Some(target), Some(path)) => (target, path),
+        _ => {
+            eprintln!("cách dùng: vipl translate --to <gói> <tệp>");
+            std::process::exit(1)
+        }
+    };
+    let file = read_source(&path, &flags);
+    let from = load_pack(&parse::source_pack(&file).unwrap_or(DEFAULT_PACK.to_string()));
+    let file = parse::resolve_accents(&file, &from).unwrap_or_else(|e| {
+        eprintln!("lỗi: {}", e);
+        std::process::exit(1)
+    });
+    match parse::translate(&file, &from, &load_pack(&target), &target) {
+        Ok(result) => print!("{}", result),
+        Err(errors) => {
+            for e in &errors {
+                eprintln!("lỗi: {}", e);
+            }
+            std::process::exit(1)
+        }
+    }
+}
 
 fn main() {
-    let args = std::env::args().collect::<Vec<String>>();
+    if std::env::args().nth(1).as_deref() == Some("translate") {
+        translate(&std::env::args().skip(2).collect::<Vec<_>>());
+        return;
+    }
+    let (flags, args): (Vec<String>, Vec<String>) =
+        std::env::args().partition(|a| a.starts_with("--"));
+    let option = compiler::CompileOption {
+        // `--wrapping-arithmetic` turn off the overflow check of `+ - *`
+        wrapping_arithmetic: flags.iter().any(|f| f == "--wrapping-arithmetic"),
+    };
     if args.len() < 3 {
         panic!("i need the file and output dir");
     }
 
-    let file = std::fs::read_to_string(args[1].clone()).unwrap();
-    let config = parse::parse_config(
-        std::fs::read_to_string(
-            "/home/tritranduc/dev/code/vietnamese-program-language/config/keyword.config"
-                .to_string(),
-        )
-        .unwrap(),
-    );
-    let ast = parse::parse_string_to_ast(file.clone(), &config);
+    let file = read_source(&args[1], &flags);
+    let config = load_pack(&parse::source_pack(&file).unwrap_or(DEFAULT_PACK.to_string()));
+    let file = parse::resolve_accents(&file, &config).unwrap_or_else(|e| {
+        eprintln!("lỗi: {}", e);
+        std::process::exit(1)
+    });
+    let mut ast = parse::parse_string_to_ast(file.clone(), &config).unwrap_or_else(|errors| {
+        for e in &errors {
+            eprintln!("{}", e.report(&file));
+        }
+        std::process::exit(1)
+    });
+    if let Err(errors) = resolve::resolve_ast(&ast, &config) {
+        for e in &errors {
+            eprintln!("{}", e.report(&file));
+        }
+        std::process::exit(1);
+    }
+    if let Err(errors) = type_check::check_ast(&mut ast, &config) {
+        for e in &errors {
+            eprintln!("{}", e.report(&file));
+        }
+        std::process::exit(1);
+    }
     let context = inkwell::context::Context::create();
     let mut code_compiler = compiler::Compiler::new(
         &context,
@@ -24,6 +121,7 @@ fn main() {
         context.create_builder(),
         &ast,
         &config,
+        option,
     );
     code_compiler.parse_ast_to_module();
     code_compiler
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::{translate::rewrite_words, KeywordConfig};
+
+/// `in_ra_màn_hình` → `in_ra_man_hinh`
+pub fn strip_accents(data: &str) -> String {
+    data.nfd()
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| match c {
+            'đ' => 'd',
+            'Đ' => 'D',
+            _ => c,
+        })
+        .collect()
+}
+
+/// every keyword by its form without accents, two keywords with the same form can not be
+/// told apart so the config is refused
+fn unaccented_keywords(config: &KeywordConfig) -> Result<BTreeMap<String, &str>, String> {
+    let mut result = BTreeMap::<String, &str>::new();
+    // the words of `chia cho` are matched one by one, they can be shared by many operators
+    let mut operator_words = config
+        .operators
+        .keys()
+        .flat_map(|k| k.split_whitespace())
+        .collect::<Vec<_>>();
+    operator_words.sort();
+    operator_words.dedup();
+    for keyword in config
+        .default_function
+        .keys()
+        .map(String::as_str)
+        .chain(operator_words)
+    {
+        let unaccented = strip_accents(keyword);
+        if let Some(other) = result.insert(unaccented.clone(), keyword) {
+            return Err(format!(
+                "từ khoá `{}` và `{}` đều viết không dấu là `{}`, không dùng được chế độ không dấu",
+                other, keyword, unaccented
+            ));
+        }
+    }
+    Ok(result)
+}
+
+/// when `accent_insensitive` is on, a word typed without any accent is replaced by the
+/// keyword it stand for: `in_ra_man_hinh` become `in_ra_màn_hình`. A word with accents of
+/// its own is a name of the program and is kept: `hàng` is not `hằng`. String literals
+/// and comments are kept as they are, and the number of characters does not change so
+/// the spans still point at the source
+pub fn resolve_accents(source: &str, config: &KeywordConfig) -> Result<String, String> {
+    if !config.accent_insensitive {
+        return Ok(source.to_string());
+    }
+    let keywords = unaccented_keywords(config)?;
+    Ok(rewrite_words(source, &[], |word| {
+        match keywords.get(word).filter(|_| strip_accents(word) == word) {
+            Some(keyword) => keyword.to_string(),
+            None => word.to_string(),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::test_pack;
+
+    fn resolve(source: &str) -> String {
+        let mut config = test_pack("vi");
+        config.accent_insensitive = true;
+        resolve_accents(source, &config).unwrap()
+    }
+
+    #[test]
+    fn words_without_accents_become_keywords() {
+        assert_eq!(
+            resolve("tao_bien x = 1 chia cho 2\nin_ra_man_hinh: x\n"),
+            "tạo_biến x = 1 chia cho 2\nin_ra_màn_hình: x\n"
+        );
+        assert_eq!(resolve("hang k = 1 cong 2\n"), "hằng k = 1 cộng 2\n");
+    }
+
+    #[test]
+    fn words_with_their_own_accents_are_kept() {
+        assert_eq!(
+            resolve("var hàng = 1\nvar chó = hàng chia cho 2\n"),
+            "var hàng = 1\nvar chó = hàng chia cho 2\n"
+        );
+        // an accent on the wrong letter is another word too
+        assert_eq!(resolve("var hêt = 1\n"), "var hêt = 1\n");
+    }
+
+    #[test]
+    fn strings_and_comments_are_kept() {
+        assert_eq!(
+            resolve("in: \"in_ra_man_hinh\" # hang\n"),
+            "in: \"in_ra_man_hinh\" # hang\n"
+        );
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let config = test_pack("vi");
+        assert_eq!(
+            resolve_accents("hang k = 1\n", &config).unwrap(),
+            "hang k = 1\n"
+        );
+    }
+}
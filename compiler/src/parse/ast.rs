@@ -0,0 +1,229 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Span, ValueType};
+
+/// a program. Its statements and expressions are stored in flat lists and point at each
+/// other with `StmtId` and `ExprId`, so a big program is a few big allocations and no
+/// node is ever copied or parsed twice
+#[derive(Debug, Default, Clone)]
+pub struct Ast {
+    /// the statements outside of any block
+    pub body: Vec<StmtId>,
+    stmts: Vec<Stmt>,
+    exprs: Vec<Expr>,
+    /// the text of the statements one after the other, `Expr::raw` is a range of it
+    text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StmtId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExprId(u32);
+
+/// a range of bytes of `Ast::text`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Ast {
+    pub fn push_stmt(&mut self, stmt: Stmt) -> StmtId {
+        self.stmts.push(stmt);
+        StmtId(self.stmts.len() as u32 - 1)
+    }
+    pub fn push_expr(&mut self, expr: Expr) -> ExprId {
+        self.exprs.push(expr);
+        ExprId(self.exprs.len() as u32 - 1)
+    }
+    /// keep the text of a statement, its expressions are ranges of it
+    pub fn push_text(&mut self, text: &str) -> TextRange {
+        let start = self.text.len();
+        self.text.push_str(text);
+        TextRange {
+            start,
+            end: self.text.len(),
+        }
+    }
+    pub fn text(&self, range: TextRange) -> &str {
+        &self.text[range.start..range.end]
+    }
+    /// the text of an expression as it was written, for the error messages
+    pub fn raw(&self, id: ExprId) -> &str {
+        self.text(self[id].raw)
+    }
+}
+
+impl Index<StmtId> for Ast {
+    type Output = Stmt;
+    fn index(&self, id: StmtId) -> &Stmt {
+        &self.stmts[id.0 as usize]
+    }
+}
+
+impl IndexMut<StmtId> for Ast {
+    fn index_mut(&mut self, id: StmtId) -> &mut Stmt {
+        &mut self.stmts[id.0 as usize]
+    }
+}
+
+impl Index<ExprId> for Ast {
+    type Output = Expr;
+    fn index(&self, id: ExprId) -> &Expr {
+        &self.exprs[id.0 as usize]
+    }
+}
+
+impl IndexMut<ExprId> for Ast {
+    fn index_mut(&mut self, id: ExprId) -> &mut Expr {
+        &mut self.exprs[id.0 as usize]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    /// `var tuổi: số_nguyên = 20`, or `hằng` when `constant`. A constant always has a
+    /// value, the parser refuse one without
+    Let {
+        name: String,
+        constant: bool,
+        /// type name written after `:`
+        annotation: Option<String>,
+        value: Option<ExprId>,
+        /// the `##` comment lines written right before the declaration
+        doc: Option<String>,
+        /// type of the declared binding, filled by the type checker
+        value_type: Option<ValueType>,
+        span: Span,
+    },
+    /// `tuổi = tuổi + 1`
+    Assign {
+        name: String,
+        value: ExprId,
+        /// type of the variable, filled by the type checker
+        value_type: Option<ValueType>,
+        span: Span,
+    },
+    /// `in: "xin chào"`
+    Call { call: Call, span: Span },
+    /// `khối ... hết`, the variables declared inside are gone after the block
+    Block { body: Vec<StmtId>, span: Span },
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Let { span, .. }
+            | Stmt::Assign { span, .. }
+            | Stmt::Call { span, .. }
+            | Stmt::Block { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub function: String,
+    pub args: Vec<ExprId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    /// where the expression is written in `Ast::text`, see `Ast::raw`
+    pub raw: TextRange,
+    /// filled by the type checker so the compiler can pick the right LLVM type,
+    /// signedness and printf format
+    pub value_type: Option<ValueType>,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, raw: TextRange) -> Self {
+        Self {
+            kind,
+            raw,
+            value_type: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    String(String),
+    /// every integer literal is a `số_nguyên` 64 bit until the type checker give it the
+    /// type it is assigned to. The value is kept wider than any of them so
+    /// `18446744073709551615` can still become a `số_tự_nhiên_64`, the type checker tell
+    /// whether it fit
+    Int(i128),
+    Float(f64),
+    /// a `thập_phân` literal like `15000.5đ`, already multiplied by `DECIMAL_SCALE`
+    Decimal(i64),
+    Bool(bool),
+    Variable(String),
+    Binary {
+        op: BinaryOp,
+        left: ExprId,
+        right: ExprId,
+    },
+    /// `làm_tròn(x, 2)`, or the name of a number type to convert: `số_nguyên(x)`
+    Call(Call),
+    /// `s[i]`, the character of a string or the string of a list at `i`
+    Index {
+        target: ExprId,
+        index: ExprId,
+    },
+    /// `s[a..b]`, no end mean to the end of the string
+    Slice {
+        target: ExprId,
+        start: ExprId,
+        end: Option<ExprId>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    /// `%`, the sign of the result is the sign of the left side like in C
+    Remainder,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl BinaryOp {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(Self::Plus),
+            "-" => Some(Self::Minus),
+            "*" => Some(Self::Times),
+            "/" => Some(Self::Divide),
+            "%" => Some(Self::Remainder),
+            "<" => Some(Self::Less),
+            "<=" => Some(Self::LessEqual),
+            ">" => Some(Self::Greater),
+            ">=" => Some(Self::GreaterEqual),
+            "==" => Some(Self::Equal),
+            "!=" => Some(Self::NotEqual),
+            _ => None,
+        }
+    }
+    /// a comparison take two numbers and give a `logic`
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Less
+                | Self::LessEqual
+                | Self::Greater
+                | Self::GreaterEqual
+                | Self::Equal
+                | Self::NotEqual
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use super::ValueType;
+
+const TEXT: &[ValueType] = &[ValueType::String, ValueType::Char];
+const LIST: &[ValueType] = &[ValueType::List];
+const NUMBER: &[ValueType] = &[
+    ValueType::Int,
+    ValueType::Int8,
+    ValueType::Int16,
+    ValueType::Int32,
+    ValueType::UInt8,
+    ValueType::UInt16,
+    ValueType::UInt32,
+    ValueType::UInt64,
+    ValueType::Float,
+    ValueType::Decimal,
+];
+
+/// functions of the standard library, their Vietnamese names come from the config like
+/// `độ_dài=string_length` and the work is done by the runtime library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinFunction {
+    Length,
+    Upper,
+    Lower,
+    Trim,
+    Split,
+    Join,
+    Replace,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Find,
+    StripDiacritics,
+    ToneNewStyle,
+    ToneOldStyle,
+    FromTelex,
+    ToTelex,
+    FromVni,
+    ToVni,
+    Compare,
+    Sort,
+    NumberWords,
+}
+
+impl BuiltinFunction {
+    pub fn from_config_type(data: &str) -> Option<Self> {
+        match data.trim() {
+            "string_length" => Some(Self::Length),
+            "string_upper" => Some(Self::Upper),
+            "string_lower" => Some(Self::Lower),
+            "string_trim" => Some(Self::Trim),
+            "string_split" => Some(Self::Split),
+            "string_join" => Some(Self::Join),
+            "string_replace" => Some(Self::Replace),
+            "string_contains" => Some(Self::Contains),
+            "string_starts_with" => Some(Self::StartsWith),
+            "string_ends_with" => Some(Self::EndsWith),
+            "string_find" => Some(Self::Find),
+            "text_strip_diacritics" => Some(Self::StripDiacritics),
+            "text_tone_new_style" => Some(Self::ToneNewStyle),
+            "text_tone_old_style" => Some(Self::ToneOldStyle),
+            "text_from_telex" => Some(Self::FromTelex),
+            "text_to_telex" => Some(Self::ToTelex),
+            "text_from_vni" => Some(Self::FromVni),
+            "text_to_vni" => Some(Self::ToVni),
+            "string_compare" => Some(Self::Compare),
+            "list_sort" => Some(Self::Sort),
+            "number_words" => Some(Self::NumberWords),
+            _ => None,
+        }
+    }
+    /// the types each parameter accept
+    pub fn parameters(&self) -> &'static [&'static [ValueType]] {
+        match self {
+            Self::Length => &[&[ValueType::String, ValueType::Char, ValueType::List]],
+            Self::Upper
+            | Self::Lower
+            | Self::Trim
+            | Self::StripDiacritics
+            | Self::ToneNewStyle
+            | Self::ToneOldStyle
+            | Self::FromTelex
+            | Self::ToTelex
+            | Self::FromVni
+            | Self::ToVni => &[TEXT],
+            Self::Split
+            | Self::Contains
+            | Self::StartsWith
+            | Self::EndsWith
+            | Self::Find
+            | Self::Compare => &[TEXT, TEXT],
+            Self::Join => &[LIST, TEXT],
+            Self::Sort => &[LIST],
+            Self::NumberWords => &[NUMBER],
+            Self::Replace => &[TEXT, TEXT, TEXT],
+        }
+    }
+    pub fn return_type(&self) -> ValueType {
+        match self {
+            Self::Length | Self::Find | Self::Compare => ValueType::Int,
+            Self::Upper
+            | Self::Lower
+            | Self::Trim
+            | Self::Join
+            | Self::Replace
+            | Self::StripDiacritics
+            | Self::ToneNewStyle
+            | Self::ToneOldStyle
+            | Self::FromTelex
+            | Self::ToTelex
+            | Self::FromVni
+            | Self::ToVni
+            | Self::NumberWords => ValueType::String,
+            Self::Split | Self::Sort => ValueType::List,
+            Self::Contains | Self::StartsWith | Self::EndsWith => ValueType::Bool,
+        }
+    }
+    /// name of the function in the runtime library, the length of a list is not counted
+    /// like the length of a string and every kind of number is read by its own function
+    pub fn runtime_name(&self, first_argument: Option<ValueType>) -> &'static str {
+        match self {
+            Self::Length if first_argument == Some(ValueType::List) => "vipl_list_length",
+            Self::Length => "vipl_string_length",
+            Self::Upper => "vipl_string_upper",
+            Self::Lower => "vipl_string_lower",
+            Self::Trim => "vipl_string_trim",
+            Self::Split => "vipl_string_split",
+            Self::Join => "vipl_list_join",
+            Self::Replace => "vipl_string_replace",
+            Self::Contains => "vipl_string_contains",
+            Self::StartsWith => "vipl_string_starts_with",
+            Self::EndsWith => "vipl_string_ends_with",
+            Self::Find => "vipl_string_find",
+            Self::StripDiacritics => "vipl_text_strip_diacritics",
+            Self::ToneNewStyle => "vipl_text_tone_new_style",
+            Self::ToneOldStyle => "vipl_text_tone_old_style",
+            Self::FromTelex => "vipl_text_from_telex",
+            Self::ToTelex => "vipl_text_to_telex",
+            Self::FromVni => "vipl_text_from_vni",
+            Self::ToVni => "vipl_text_to_vni",
+            Self::Compare => "vipl_string_compare",
+            Self::Sort => "vipl_list_sort",
+            Self::NumberWords => match first_argument {
+                Some(ValueType::Float) => "vipl_number_words_float",
+                Some(ValueType::Decimal) => "vipl_number_words_decimal",
+                Some(t) if t.int_info().is_some_and(|(_, signed)| !signed) => {
+                    "vipl_number_words_uint"
+                }
+                _ => "vipl_number_words_int",
+            },
+        }
+    }
+}
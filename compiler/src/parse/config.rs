@@ -0,0 +1,446 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{
+    normalize_source, BlockStyle, BuiltinFunction, DefaultFunctionType, KeywordConfig,
+    RoundingMode, ValueType,
+};
+
+lazy_static! {
+    /// a keyword must be a word the parser can read, the same `\w+` as its regexes
+    static ref KEYWORD_REGEX: Regex = Regex::new(r"^\w+$").unwrap();
+    /// an operator can have many words: `"chia cho" = "/"`
+    static ref OPERATOR_REGEX: Regex = Regex::new(r"^\w+( \w+)*$").unwrap();
+}
+
+/// the words the parser already understand, a keyword with the same name would hide them
+const RESERVED_WORDS: &[&str] = &["true", "false"];
+const OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "<", "<=", ">", ">=", "==", "!="];
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lỗi cấu hình `{}`: {}",
+            self.file.display(),
+            self.message
+        )
+    }
+}
+
+/// the tables of a config file, each one accept its own kind of value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// `in = "print"`, `var = "variable"`, `hằng = "constant"`, `"hết" = "end"`
+    Statements,
+    /// `"số_nguyên" = "int"`
+    Types,
+    /// `"độ_dài" = "string_length"`, `"làm_tròn" = "round"`
+    Builtins,
+    /// `"cộng" = "+"`
+    Operators,
+}
+
+impl Section {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "statements" => Some(Self::Statements),
+            "types" => Some(Self::Types),
+            "builtins" => Some(Self::Builtins),
+            "operators" => Some(Self::Operators),
+            _ => None,
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Statements => "statements",
+            Self::Types => "types",
+            Self::Builtins => "builtins",
+            Self::Operators => "operators",
+        }
+    }
+    fn accept(&self, value: &str) -> bool {
+        match self {
+            Self::Statements => {
+                matches!(value, "print" | "variable" | "constant" | "block" | "end")
+            }
+            Self::Types => ValueType::from_config_type(value).is_some(),
+            Self::Builtins => {
+                BuiltinFunction::from_config_type(value).is_some()
+                    || RoundingMode::from_config_type(value).is_some()
+            }
+            Self::Operators => OPERATORS.contains(&value),
+        }
+    }
+}
+
+/// what every file of a config chain add, a file override the files it extends
+#[derive(Debug, Default)]
+struct ConfigEntries {
+    keywords: BTreeMap<String, (Section, String)>,
+    accent_insensitive: bool,
+    block_style: BlockStyle,
+}
+
+/// read `keyword.toml` and the configs it extends:
+///
+/// ```toml
+/// extends = ["co_ban.toml"]
+///
+/// [options]
+/// accent_insensitive = false
+/// blocks = "keyword"
+///
+/// [statements]
+/// "in_ra_màn_hình" = "print"
+/// ```
+///
+/// every mistake is reported, not only the first one
+pub fn load_config(path: &Path) -> Result<KeywordConfig, Vec<ConfigError>> {
+    let mut entries = ConfigEntries::default();
+    let mut errors = vec![];
+    read_config_file(path, &mut vec![], &mut entries, &mut errors);
+    check_operator_words(path, &entries, &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(build_config(entries))
+}
+
+fn read_config_file(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    entries: &mut ConfigEntries,
+    errors: &mut Vec<ConfigError>,
+) {
+    let error = |message: String| ConfigError {
+        file: path.to_path_buf(),
+        message,
+    };
+    if chain.iter().any(|p| p == path) {
+        errors.push(error("cấu hình tự kế thừa chính nó".to_string()));
+        return;
+    }
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => normalize_source(&text),
+        Err(e) => {
+            errors.push(error(format!("không đọc được tệp: {}", e)));
+            return;
+        }
+    };
+    let table = match text.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(e) => {
+            errors.push(error(e.to_string()));
+            return;
+        }
+    };
+
+    // the base configs first so this file can override their keywords
+    let extends = match table.get("extends") {
+        None => vec![],
+        Some(toml::Value::String(s)) => vec![s.clone()],
+        Some(toml::Value::Array(a)) if a.iter().all(|v| v.is_str()) => a
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        Some(_) => {
+            errors.push(error(
+                "`extends` phải là một đường dẫn hoặc một danh sách đường dẫn".to_string(),
+            ));
+            vec![]
+        }
+    };
+    chain.push(path.to_path_buf());
+    let directory = path.parent().unwrap_or(Path::new("."));
+    for base in extends {
+        read_config_file(&directory.join(base), chain, entries, errors);
+    }
+    chain.pop();
+
+    let mut own = BTreeMap::<String, Section>::new();
+    for (key, value) in &table {
+        if key == "extends" {
+            continue;
+        }
+        if key == "options" {
+            read_options(value, entries, &error, errors);
+            continue;
+        }
+        let section = match (Section::from_name(key), value.as_table()) {
+            (Some(section), Some(_)) => section,
+            _ => {
+                errors.push(error(format!("không có mục `{}`", key)));
+                continue;
+            }
+        };
+        for (keyword, value) in value.as_table().unwrap() {
+            let value = match value.as_str() {
+                Some(value) if section.accept(value) => value,
+                _ => {
+                    errors.push(error(format!(
+                        "`{}` trong [{}] có loại không hợp lệ `{}`",
+                        keyword,
+                        section.name(),
+                        value
+                    )));
+                    continue;
+                }
+            };
+            let pattern: &Regex = if section == Section::Operators {
+                &OPERATOR_REGEX
+            } else {
+                &KEYWORD_REGEX
+            };
+            if !pattern.is_match(keyword) {
+                errors.push(error(format!("`{}` không phải là một từ", keyword)));
+                continue;
+            }
+            if RESERVED_WORDS.contains(&keyword.as_str()) {
+                errors.push(error(format!(
+                    "`{}` là từ dành riêng, không dùng làm từ khoá được",
+                    keyword
+                )));
+                continue;
+            }
+            if let Some(other) = own.insert(keyword.clone(), section) {
+                errors.push(error(format!(
+                    "từ khoá `{}` có ở cả [{}] và [{}]",
+                    keyword,
+                    other.name(),
+                    section.name()
+                )));
+                continue;
+            }
+            // a file can change what a keyword of its base mean but not its kind, the
+            // parser would read `in` as a statement and as a type
+            if let Some((base, _)) = entries.keywords.get(keyword) {
+                if *base != section {
+                    errors.push(error(format!(
+                        "từ khoá `{}` đã có ở [{}] trong cấu hình gốc, không chuyển sang [{}] được",
+                        keyword,
+                        base.name(),
+                        section.name()
+                    )));
+                    continue;
+                }
+            }
+            entries
+                .keywords
+                .insert(keyword.clone(), (section, value.to_string()));
+        }
+    }
+}
+
+/// the words of `"chia cho" = "/"` are read one by one, none of them can be a keyword
+/// of another section or a reserved word of the whole pack with its bases
+fn check_operator_words(path: &Path, entries: &ConfigEntries, errors: &mut Vec<ConfigError>) {
+    let operators = entries
+        .keywords
+        .iter()
+        .filter(|(_, (section, _))| *section == Section::Operators);
+    for (operator, _) in operators {
+        for word in operator.split_whitespace() {
+            let message = if RESERVED_WORDS.contains(&word) {
+                format!(
+                    "từ `{}` của phép toán `{}` là từ dành riêng",
+                    word, operator
+                )
+            } else {
+                match entries.keywords.get(word) {
+                    Some((section, _)) if *section != Section::Operators => format!(
+                        "từ `{}` của phép toán `{}` trùng với từ khoá ở [{}]",
+                        word,
+                        operator,
+                        section.name()
+                    ),
+                    _ => continue,
+                }
+            };
+            errors.push(ConfigError {
+                file: path.to_path_buf(),
+                message,
+            });
+        }
+    }
+}
+
+fn read_options(
+    value: &toml::Value,
+    entries: &mut ConfigEntries,
+    error: &dyn Fn(String) -> ConfigError,
+    errors: &mut Vec<ConfigError>,
+) {
+    let options = match value.as_table() {
+        Some(options) => options,
+        None => {
+            errors.push(error("`options` phải là một mục".to_string()));
+            return;
+        }
+    };
+    for (name, value) in options {
+        match (name.as_str(), value.as_bool()) {
+            ("accent_insensitive", Some(value)) => entries.accent_insensitive = value,
+            ("accent_insensitive", None) => errors.push(error(
+                "`accent_insensitive` phải là true hoặc false".to_string(),
+            )),
+            ("blocks", _) => match value.as_str().and_then(BlockStyle::from_config_type) {
+                Some(style) => entries.block_style = style,
+                None => errors.push(error(
+                    "`blocks` phải là \"keyword\" hoặc \"indent\"".to_string(),
+                )),
+            },
+            _ => errors.push(error(format!("không có tuỳ chọn `{}`", name))),
+        }
+    }
+}
+
+fn build_config(entries: ConfigEntries) -> KeywordConfig {
+    let mut result = KeywordConfig {
+        accent_insensitive: entries.accent_insensitive,
+        block_style: entries.block_style,
+        ..Default::default()
+    };
+    for (keyword, (section, value)) in entries.keywords {
+        if section == Section::Operators {
+            result.operators.insert(keyword, value);
+            continue;
+        }
+        result.default_function.insert(
+            keyword.clone(),
+            DefaultFunctionType {
+                name: keyword.clone(),
+                r#type: value.clone(),
+            },
+        );
+        match value.as_str() {
+            "variable" => result.variable_keyword.push(keyword),
+            "constant" => result.constant_keyword.push(keyword),
+            "block" => result.block_keyword.push(keyword),
+            "end" => result.end_keyword.push(keyword),
+            _ => {
+                if let Some(t) = ValueType::from_config_type(&value) {
+                    result.type_keyword.insert(keyword, t);
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// write the files in a directory of their own and load the first one
+    fn load(name: &str, files: &[(&str, &str)]) -> Result<KeywordConfig, Vec<String>> {
+        let directory = std::env::temp_dir().join(format!("vipl_config_{}", name));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, text) in files {
+            std::fs::write(directory.join(file), text).unwrap();
+        }
+        load_config(&directory.join(files[0].0))
+            .map_err(|errors| errors.into_iter().map(|e| e.message).collect())
+    }
+
+    #[test]
+    fn child_override_a_keyword() {
+        let config = load(
+            "override",
+            &[
+                (
+                    "child.toml",
+                    "extends = \"base.toml\"\n[statements]\nin = \"end\"\n",
+                ),
+                (
+                    "base.toml",
+                    "[statements]\nin = \"print\"\nvar = \"variable\"\n",
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.end_keyword, ["in"]);
+        assert_eq!(config.variable_keyword, ["var"]);
+    }
+
+    #[test]
+    fn child_can_not_move_a_keyword() {
+        let errors = load(
+            "move",
+            &[
+                (
+                    "child.toml",
+                    "extends = \"base.toml\"\n[types]\nin = \"int\"\n",
+                ),
+                ("base.toml", "[statements]\nin = \"print\"\n"),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            ["từ khoá `in` đã có ở [statements] trong cấu hình gốc, không chuyển sang [types] được"]
+        );
+    }
+
+    #[test]
+    fn same_keyword_in_two_sections() {
+        let errors = load(
+            "duplicate",
+            &[(
+                "pack.toml",
+                "[statements]\nin = \"print\"\n[types]\nin = \"int\"\n",
+            )],
+        )
+        .unwrap_err();
+        assert_eq!(errors, ["từ khoá `in` có ở cả [statements] và [types]"]);
+    }
+
+    #[test]
+    fn operator_words_are_not_keywords() {
+        let errors = load(
+            "operator_words",
+            &[
+                (
+                    "child.toml",
+                    "extends = \"base.toml\"\n[operators]\n\"chia cho\" = \"/\"\n",
+                ),
+                ("base.toml", "[builtins]\ncho = \"string_length\"\n"),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            ["từ `cho` của phép toán `chia cho` trùng với từ khoá ở [builtins]"]
+        );
+        let errors = load(
+            "operator_reserved",
+            &[("pack.toml", "[operators]\n\"is true\" = \"==\"\n")],
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            ["từ `true` của phép toán `is true` là từ dành riêng"]
+        );
+    }
+
+    #[test]
+    fn packs_of_the_repository_are_valid() {
+        for pack in ["vi", "en"] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(format!("../config/packs/{}.toml", pack));
+            assert!(load_config(&path).is_ok(), "{}", pack);
+        }
+    }
+}
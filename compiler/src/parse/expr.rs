@@ -0,0 +1,482 @@
+use super::{
+    literal::{self, Number},
+    Ast, BinaryOp, Call, ContextType, Expr, ExprId, ExprKind, TextRange,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// the sign is applied later, see `literal::Number::Int`
+    Int(i128),
+    Float(f64),
+    Decimal(i64),
+    String(String),
+    Ident(String),
+    Op(BinaryOp),
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    DotDot,
+    Comma,
+}
+
+/// split an expression in tokens, each with its place in the `Ast` text when `data` start
+/// at `offset`. A literal that can not be read is reported in `errors` and replaced by a
+/// placeholder so the rest is still checked
+fn tokenize(
+    data: &str,
+    offset: usize,
+    operators: &[(Vec<String>, BinaryOp)],
+    errors: &mut Vec<String>,
+) -> Option<Vec<(Token, TextRange)>> {
+    let mut result = vec![];
+    let chars = data.chars().collect::<Vec<_>>();
+    let offsets = data
+        .char_indices()
+        .map(|(i, _)| offset + i)
+        .chain(std::iter::once(offset + data.len()))
+        .collect::<Vec<_>>();
+    let range = |start: usize, end: usize| TextRange {
+        start: offsets[start],
+        end: offsets[end],
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if let Some(end) = literal::string_literal_end(&chars, i) {
+            match literal::read_string_literal(&chars, i) {
+                Ok((value, end)) => {
+                    result.push((Token::String(value), range(start, end)));
+                    i = end;
+                }
+                Err(e) => {
+                    errors.push(e);
+                    result.push((Token::String(String::new()), range(start, end)));
+                    i = end;
+                }
+            }
+        } else if c.is_ascii_digit() {
+            // a literal with a dot or an exponent is a real number, `2` and `2.0` are
+            // different types
+            match literal::read_number_literal(&chars, i) {
+                Ok((number, end)) => {
+                    let token = match number {
+                        Number::Int(value) => Token::Int(value),
+                        Number::Float(value) => Token::Float(value),
+                        Number::Decimal(value) => Token::Decimal(value),
+                    };
+                    result.push((token, range(start, end)));
+                    i = end;
+                }
+                Err(e) => {
+                    errors.push(e);
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                        i += 1;
+                    }
+                    result.push((Token::Int(0), range(start, i)));
+                }
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name = chars[start..i].iter().collect();
+            result.push((Token::Ident(name), range(start, i)));
+        } else if c == '.' && chars.get(i + 1) == Some(&'.') {
+            i += 2;
+            result.push((Token::DotDot, range(start, i)));
+        } else if let Some(op) = chars
+            .get(i..i + 2)
+            .and_then(|two| BinaryOp::from_symbol(&two.iter().collect::<String>()))
+        {
+            // `<=` before `<`
+            i += 2;
+            result.push((Token::Op(op), range(start, i)));
+        } else {
+            let token = match c {
+                '(' => Token::OpenParen,
+                '[' => Token::OpenBracket,
+                ']' => Token::CloseBracket,
+                ')' => Token::CloseParen,
+                ',' => Token::Comma,
+                _ => Token::Op(BinaryOp::from_symbol(&c.to_string())?),
+            };
+            i += 1;
+            result.push((token, range(start, i)));
+        }
+    }
+    Some(resolve_word_operators(result, operators))
+}
+
+/// `a cộng b` is `a + b`, the words of an operator are read as identifiers first and
+/// replaced here so an operator can have many words: `chia cho`
+fn resolve_word_operators(
+    tokens: Vec<(Token, TextRange)>,
+    operators: &[(Vec<String>, BinaryOp)],
+) -> Vec<(Token, TextRange)> {
+    let mut result = vec![];
+    let mut i = 0;
+    'tokens: while i < tokens.len() {
+        for (words, op) in operators {
+            let matched = words
+                .iter()
+                .enumerate()
+                .all(|(j, w)| match tokens.get(i + j) {
+                    Some((Token::Ident(name), _)) => name == w,
+                    _ => false,
+                });
+            if matched {
+                let range = TextRange {
+                    start: tokens[i].1.start,
+                    end: tokens[i + words.len() - 1].1.end,
+                };
+                result.push((Token::Op(*op), range));
+                i += words.len();
+                continue 'tokens;
+            }
+        }
+        result.push(tokens[i].clone());
+        i += 1;
+    }
+    result
+}
+
+struct ExprParser<'a> {
+    tokens: Vec<(Token, TextRange)>,
+    position: usize,
+    ast: &'a mut Ast,
+}
+
+/// parse an arithmetic expression with the usual precedence, `*`, `/` and `%` bind
+/// tighter than `+` and `-` which bind tighter than the comparisons. The nodes are added
+/// to `ast`, `data` is the text at `offset` in it
+pub fn parse_expression(
+    data: &str,
+    offset: usize,
+    context: &mut ContextType,
+    ast: &mut Ast,
+) -> Option<ExprId> {
+    let mut parser = ExprParser {
+        tokens: tokenize(data, offset, &context.operators, &mut context.errors)?,
+        position: 0,
+        ast,
+    };
+    if parser.tokens.is_empty() {
+        return None;
+    }
+    let expr = parser.parse_comparison()?;
+    if parser.position != parser.tokens.len() {
+        return None;
+    }
+    Some(expr)
+}
+
+impl<'a> ExprParser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    /// the text from the token at `start` to the last token read
+    fn range_from(&self, start: usize) -> TextRange {
+        TextRange {
+            start: self.tokens[start].1.start,
+            end: self.tokens[self.position - 1].1.end,
+        }
+    }
+
+    fn push(&mut self, kind: ExprKind, start: usize) -> ExprId {
+        let raw = self.range_from(start);
+        self.ast.push_expr(Expr::new(kind, raw))
+    }
+
+    fn binary_expr(&mut self, op: BinaryOp, left: ExprId, right: ExprId, start: usize) -> ExprId {
+        self.push(ExprKind::Binary { op, left, right }, start)
+    }
+
+    /// `a + 1 lớn hơn b`, a comparison can not be chained: `a < b < c` is not read
+    fn parse_comparison(&mut self) -> Option<ExprId> {
+        let start = self.position;
+        let left = self.parse_sum()?;
+        match self.peek().cloned() {
+            Some(Token::Op(op)) if op.is_comparison() => {
+                self.next();
+                let right = self.parse_sum()?;
+                Some(self.binary_expr(op, left, right, start))
+            }
+            _ => Some(left),
+        }
+    }
+
+    fn parse_sum(&mut self) -> Option<ExprId> {
+        let start = self.position;
+        let mut left = self.parse_product()?;
+        while let Some(Token::Op(op)) = self.peek().cloned() {
+            if !matches!(op, BinaryOp::Plus | BinaryOp::Minus) {
+                break;
+            }
+            self.next();
+            let right = self.parse_product()?;
+            left = self.binary_expr(op, left, right, start);
+        }
+        Some(left)
+    }
+
+    fn parse_product(&mut self) -> Option<ExprId> {
+        let start = self.position;
+        let mut left = self.parse_unary()?;
+        while let Some(Token::Op(op)) = self.peek().cloned() {
+            if !matches!(op, BinaryOp::Times | BinaryOp::Divide | BinaryOp::Remainder) {
+                break;
+            }
+            self.next();
+            let right = self.parse_unary()?;
+            left = self.binary_expr(op, left, right, start);
+        }
+        Some(left)
+    }
+
+    fn parse_unary(&mut self) -> Option<ExprId> {
+        let start = self.position;
+        if let Some(Token::Op(BinaryOp::Minus)) = self.peek() {
+            self.next();
+            return match self.peek().cloned() {
+                // keep `-1.5` a literal instead of `0 - 1.5`
+                Some(Token::Int(i)) => {
+                    self.next();
+                    Some(self.push(ExprKind::Int(-i), start))
+                }
+                Some(Token::Float(f)) => {
+                    self.next();
+                    Some(self.push(ExprKind::Float(-f), start))
+                }
+                Some(Token::Decimal(d)) => {
+                    self.next();
+                    Some(self.push(ExprKind::Decimal(-d), start))
+                }
+                _ => {
+                    // the `0` is written as the `-`
+                    let zero = self.push(ExprKind::Int(0), start);
+                    let value = self.parse_unary()?;
+                    Some(self.binary_expr(BinaryOp::Minus, zero, value, start))
+                }
+            };
+        }
+        let expr = self.parse_primary()?;
+        self.parse_index(expr, start)
+    }
+
+    /// `s[1]`, `s[1..3]`, `s[..3]` and `s[1..]` after a value
+    fn parse_index(&mut self, mut expr: ExprId, start: usize) -> Option<ExprId> {
+        while let Some(Token::OpenBracket) = self.peek() {
+            self.next();
+            let index = match self.peek()? {
+                Token::DotDot => {
+                    // the `0` is written as the `..`
+                    let raw = self.tokens[self.position].1;
+                    self.ast.push_expr(Expr::new(ExprKind::Int(0), raw))
+                }
+                _ => self.parse_sum()?,
+            };
+            let target = expr;
+            expr = match self.next()? {
+                Token::CloseBracket => self.push(ExprKind::Index { target, index }, start),
+                Token::DotDot => {
+                    let end = match self.peek() {
+                        Some(Token::CloseBracket) => None,
+                        _ => Some(self.parse_sum()?),
+                    };
+                    match self.next()? {
+                        Token::CloseBracket => {}
+                        _ => return None,
+                    }
+                    let kind = ExprKind::Slice {
+                        target,
+                        start: index,
+                        end,
+                    };
+                    self.push(kind, start)
+                }
+                _ => return None,
+            };
+        }
+        Some(expr)
+    }
+
+    fn parse_primary(&mut self) -> Option<ExprId> {
+        let start = self.position;
+        match self.next()? {
+            Token::Int(i) => Some(self.push(ExprKind::Int(i), start)),
+            Token::Float(f) => Some(self.push(ExprKind::Float(f), start)),
+            Token::Decimal(d) => Some(self.push(ExprKind::Decimal(d), start)),
+            Token::String(s) => Some(self.push(ExprKind::String(s), start)),
+            Token::OpenParen => {
+                let expr = self.parse_comparison()?;
+                match self.next()? {
+                    Token::CloseParen => {
+                        // the parentheses are part of the text of the value
+                        self.ast[expr].raw = self.range_from(start);
+                        Some(expr)
+                    }
+                    _ => None,
+                }
+            }
+            Token::Ident(name) => {
+                if let Some(Token::OpenParen) = self.peek() {
+                    self.next();
+                    return self.parse_call(name, start);
+                }
+                let kind = match name.as_str() {
+                    "true" => ExprKind::Bool(true),
+                    "false" => ExprKind::Bool(false),
+                    // checked by the resolver, the parser does not know the names
+                    _ => ExprKind::Variable(name),
+                };
+                Some(self.push(kind, start))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_call(&mut self, function: String, start: usize) -> Option<ExprId> {
+        let mut args = vec![];
+        if let Some(Token::CloseParen) = self.peek() {
+            self.next();
+        } else {
+            loop {
+                args.push(self.parse_comparison()?);
+                match self.next()? {
+                    Token::Comma => continue,
+                    Token::CloseParen => break,
+                    _ => return None,
+                }
+            }
+        }
+        Some(self.push(ExprKind::Call(Call { function, args }), start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_string_to_ast, test_pack, Stmt};
+
+    fn tokens(data: &str) -> Vec<Token> {
+        let context = ContextType::new(&test_pack("vi"));
+        let mut errors = vec![];
+        let tokens = tokenize(data, 0, &context.operators, &mut errors).unwrap();
+        assert_eq!(errors, Vec::<String>::new());
+        tokens.into_iter().map(|(token, _)| token).collect()
+    }
+
+    /// the tree of an expression with its parentheses written out
+    fn show(ast: &Ast, id: ExprId) -> String {
+        match &ast[id].kind {
+            ExprKind::Int(i) => i.to_string(),
+            ExprKind::Variable(name) => name.clone(),
+            &ExprKind::Binary { op, left, right } => {
+                format!("({} {:?} {})", show(ast, left), op, show(ast, right))
+            }
+            kind => format!("{:?}", kind),
+        }
+    }
+
+    /// `var x = <data>` parsed and the tree of its value with the text it was read from
+    fn parse(data: &str) -> (String, String) {
+        let source = format!("var x = {}\n", data);
+        let ast = parse_string_to_ast(source, &test_pack("vi")).unwrap();
+        let Stmt::Let {
+            value: Some(value), ..
+        } = ast[ast.body[0]]
+        else {
+            panic!("`{}` không phải là khai báo", data)
+        };
+        (show(&ast, value), ast.raw(value).to_string())
+    }
+
+    fn parse_error(data: &str) -> String {
+        let source = format!("var x = {}\n", data);
+        let errors = parse_string_to_ast(source, &test_pack("vi")).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        errors[0].message.clone()
+    }
+
+    #[test]
+    fn words_become_operators() {
+        let ident = |name: &str| Token::Ident(name.to_string());
+        assert_eq!(
+            tokens("a cộng b trừ 2"),
+            [
+                ident("a"),
+                Token::Op(BinaryOp::Plus),
+                ident("b"),
+                Token::Op(BinaryOp::Minus),
+                Token::Int(2)
+            ]
+        );
+        assert_eq!(tokens("a chia cho b"), tokens("a / b"));
+        assert_eq!(tokens("a chia b"), tokens("a / b"));
+        assert_eq!(tokens("a chia lấy dư b"), tokens("a % b"));
+        assert_eq!(tokens("a nhân với b"), tokens("a * b"));
+        assert_eq!(tokens("a lớn hơn b"), tokens("a > b"));
+        assert_eq!(tokens("a lớn hơn hoặc bằng b"), tokens("a >= b"));
+        assert_eq!(tokens("a nhỏ hơn hoặc bằng b"), tokens("a <= b"));
+        assert_eq!(tokens("a bằng b"), tokens("a == b"));
+        assert_eq!(tokens("a khác b"), tokens("a != b"));
+    }
+
+    #[test]
+    fn only_the_whole_words_are_operators() {
+        // `lớn` alone or inside a name is not `lớn hơn`
+        assert_eq!(tokens("lớn"), [Token::Ident("lớn".to_string())]);
+        assert_eq!(tokens("cộng_dồn"), [Token::Ident("cộng_dồn".to_string())]);
+        // `chia` is an operator of its own, the rest of `chia lấy dư` is a name
+        assert_eq!(
+            tokens("chia lấy"),
+            [Token::Op(BinaryOp::Divide), Token::Ident("lấy".to_string())]
+        );
+        // a word in a string is text
+        assert_eq!(tokens("\"cộng\""), [Token::String("cộng".to_string())]);
+    }
+
+    #[test]
+    fn word_operators_keep_the_precedence() {
+        assert_eq!(
+            parse("1 cộng 2 nhân với 3 lớn hơn y"),
+            (
+                "((1 Plus (2 Times 3)) Greater y)".to_string(),
+                "1 cộng 2 nhân với 3 lớn hơn y".to_string()
+            )
+        );
+        assert_eq!(
+            parse("(a trừ b) chia lấy dư 2"),
+            (
+                "((a Minus b) Remainder 2)".to_string(),
+                "(a trừ b) chia lấy dư 2".to_string()
+            )
+        );
+        assert_eq!(parse("a trừ -b").0, "(a Minus (0 Minus b))");
+    }
+
+    #[test]
+    fn misused_word_operators() {
+        assert_eq!(
+            parse_error("1 chia lấy 2"),
+            "không hiểu giá trị `1 chia lấy 2`"
+        );
+        assert_eq!(
+            parse_error("1 lớn hơn 2 nhỏ hơn 3"),
+            "không hiểu giá trị `1 lớn hơn 2 nhỏ hơn 3`"
+        );
+    }
+}
@@ -0,0 +1,454 @@
+use std::num::IntErrorKind;
+
+/// how a string literal is quoted
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    /// `r"C:\thư_mục"`, the backslashes are kept as they are
+    raw: bool,
+    /// `"""` ... `"""`, the literal can span many lines
+    triple: bool,
+}
+
+impl Quote {
+    /// the quote of a string literal starting at `chars[i]` and how many chars it take
+    fn at(chars: &[char], i: usize) -> Option<(Self, usize)> {
+        let raw = chars.get(i) == Some(&'r')
+            && (i == 0 || !is_word_char(chars[i - 1]))
+            && chars.get(i + 1) == Some(&'"');
+        let quote = i + usize::from(raw);
+        if chars.get(quote) != Some(&'"') {
+            return None;
+        }
+        let triple = chars.get(quote + 1) == Some(&'"') && chars.get(quote + 2) == Some(&'"');
+        let width = usize::from(raw) + if triple { 3 } else { 1 };
+        Some((Self { raw, triple }, width))
+    }
+    fn closed_at(&self, chars: &[char], i: usize) -> bool {
+        chars.get(i) == Some(&'"')
+            && (!self.triple || (chars.get(i + 1) == Some(&'"') && chars.get(i + 2) == Some(&'"')))
+    }
+    fn width(&self) -> usize {
+        if self.triple {
+            3
+        } else {
+            1
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// the position after the string literal starting at `chars[i]`, `None` when no literal
+/// start there. A literal that is not closed stop at the end of its line, or at the end of
+/// the text for `"""`
+pub(crate) fn string_literal_end(chars: &[char], i: usize) -> Option<usize> {
+    let (quote, width) = Quote::at(chars, i)?;
+    let mut j = i + width;
+    while j < chars.len() {
+        if quote.closed_at(chars, j) {
+            return Some(j + quote.width());
+        }
+        if chars[j] == '\n' && !quote.triple {
+            return Some(j);
+        }
+        j += if chars[j] == '\\' && !quote.raw { 2 } else { 1 };
+    }
+    Some(chars.len())
+}
+
+/// read the string literal starting at `chars[i]`, the escapes are turned into the
+/// characters they stand for here and nowhere else:
+///
+/// - `"a\tb"` with `\n \t \r \\ \"` and `\u{1EC7}` for any Unicode character
+/// - `r"C:\thư_mục"` keep its backslashes
+/// - `"""` ... `"""` span many lines, a line break right after the opening quotes is
+///   not part of the string
+///
+/// give the string and the position after the literal
+pub(crate) fn read_string_literal(chars: &[char], i: usize) -> Result<(String, usize), String> {
+    let (quote, width) = Quote::at(chars, i).ok_or("không phải là một chuỗi".to_string())?;
+    let mut result = String::new();
+    let mut j = i + width;
+    if quote.triple && chars.get(j) == Some(&'\n') {
+        j += 1;
+    }
+    loop {
+        if quote.closed_at(chars, j) {
+            return Ok((result, j + quote.width()));
+        }
+        match chars.get(j) {
+            None => {
+                return Err(if quote.triple {
+                    "chuỗi `\"\"\"` chưa được đóng đến hết tệp".to_string()
+                } else {
+                    "chuỗi chưa được đóng bằng `\"`".to_string()
+                })
+            }
+            Some('\n') if !quote.triple => {
+                return Err(
+                    "chuỗi chưa được đóng bằng `\"`, dùng `\"\"\"` cho chuỗi nhiều dòng"
+                        .to_string(),
+                )
+            }
+            Some('\\') if !quote.raw => {
+                let (c, length) = read_escape(&chars[j + 1..])?;
+                result.push(c);
+                j += 1 + length;
+            }
+            Some(c) => {
+                result.push(*c);
+                j += 1;
+            }
+        }
+    }
+}
+
+/// the character of the escape after a `\` and how many chars it take
+fn read_escape(chars: &[char]) -> Result<(char, usize), String> {
+    match chars.first() {
+        Some('n') => Ok(('\n', 1)),
+        Some('t') => Ok(('\t', 1)),
+        Some('r') => Ok(('\r', 1)),
+        Some('\\') => Ok(('\\', 1)),
+        Some('"') => Ok(('"', 1)),
+        Some('u') => {
+            let end = chars.iter().position(|c| *c == '}');
+            let code = match (chars.get(1), end) {
+                (Some('{'), Some(end)) => chars[2..end].iter().collect::<String>(),
+                _ => return Err("kí tự thoát `\\u` phải có dạng `\\u{1EC7}`".to_string()),
+            };
+            u32::from_str_radix(&code, 16)
+                .ok()
+                .filter(|_| (1..=6).contains(&code.len()))
+                .and_then(char::from_u32)
+                .map(|c| (c, end.unwrap() + 1))
+                .ok_or(format!("`\\u{{{}}}` không phải là một kí tự Unicode", code))
+        }
+        Some(c) => Err(format!(
+            "kí tự thoát `\\{}` không hợp lệ, chỉ có `\\n \\t \\r \\\\ \\\"` và `\\u{{...}}`",
+            c
+        )),
+        None => Err("chuỗi kết thúc bằng `\\`".to_string()),
+    }
+}
+
+/// the value of a number literal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Number {
+    /// wider than `i64` so `-9223372036854775808` can be read before its sign is applied,
+    /// and a `số_tự_nhiên_64` up to `18446744073709551615`
+    Int(i128),
+    Float(f64),
+    /// `15_000.5đ`, already multiplied by `DECIMAL_SCALE`
+    Decimal(i64),
+}
+
+/// read the number literal starting at `chars[i]`:
+///
+/// - `255`, `0xFF`, `0o377` and `0b1111_1111` are integers
+/// - `2.5`, `1e-3` and `6.02e23` are real numbers, an exponent always give a real number
+/// - `15_000.5đ` is a `thập_phân`
+///
+/// `_` can separate the digits of all of them. Give the number and the position after it
+pub(crate) fn read_number_literal(chars: &[char], i: usize) -> Result<(Number, usize), String> {
+    let radix = match (chars.get(i), chars.get(i + 1)) {
+        (Some('0'), Some('x' | 'X')) => 16,
+        (Some('0'), Some('o' | 'O')) => 8,
+        (Some('0'), Some('b' | 'B')) => 2,
+        _ => 10,
+    };
+    let mut j = i;
+    let number = if radix != 10 {
+        j += 2;
+        let start = j;
+        while j < chars.len() && is_word_char(chars[j]) {
+            j += 1;
+        }
+        let raw = chars[i..j].iter().collect::<String>();
+        let digits = digit_group(&chars[start..j], &raw)?;
+        match i128::from_str_radix(&digits, radix) {
+            Ok(value) => Number::Int(value),
+            Err(e) if *e.kind() == IntErrorKind::PosOverflow => Number::Int(i128::MAX),
+            Err(_) => return Err(format!("`{}` không phải là một số hệ {}", raw, radix)),
+        }
+    } else {
+        let start = j;
+        j = skip_digits(chars, j);
+        let integer = j;
+        let mut fraction = None;
+        if chars.get(j) == Some(&'.') && chars.get(j + 1).is_some_and(|c| c.is_ascii_digit()) {
+            fraction = Some(j + 1);
+            j = skip_digits(chars, j + 1);
+        }
+        let mut exponent = None;
+        if matches!(chars.get(j), Some('e' | 'E')) {
+            let sign = usize::from(matches!(chars.get(j + 1), Some('+' | '-')));
+            if chars.get(j + 1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                exponent = Some(j);
+                j = skip_digits(chars, j + 1 + sign);
+            }
+        }
+        let raw = chars[start..j].iter().collect::<String>();
+        let mut digits = digit_group(&chars[start..integer], &raw)?;
+        if let Some(fraction) = fraction {
+            let end = exponent.unwrap_or(j);
+            digits = format!("{}.{}", digits, digit_group(&chars[fraction..end], &raw)?);
+        }
+        if let Some(exponent) = exponent {
+            let sign = usize::from(matches!(chars[exponent + 1], '+' | '-'));
+            digits = format!(
+                "{}e{}{}",
+                digits,
+                if chars[exponent + 1] == '-' { "-" } else { "" },
+                digit_group(&chars[exponent + 1 + sign..j], &raw)?
+            );
+        }
+        // `0.1đ` is a `thập_phân`, exact unlike the float `0.1`
+        if chars.get(j) == Some(&'đ') && !chars.get(j + 1).is_some_and(|c| is_word_char(*c)) {
+            j += 1;
+            if exponent.is_some() {
+                return Err(format!("số thập phân `{}đ` không viết được với số mũ", raw));
+            }
+            Number::Decimal(super::parse_decimal_literal(&digits).ok_or(format!(
+                "số thập phân `{}đ` quá lớn hoặc có nhiều hơn {} chữ số sau dấu chấm",
+                raw,
+                super::DECIMAL_DIGITS
+            ))?)
+        } else if fraction.is_some() || exponent.is_some() {
+            let value = digits.parse::<f64>().unwrap();
+            if value.is_infinite() {
+                return Err(format!("số `{}` quá lớn, không vừa với số thực", raw));
+            }
+            Number::Float(value)
+        } else {
+            // more than 38 digits do not fit in i128 either, it is too big all the same
+            Number::Int(digits.parse::<i128>().unwrap_or(i128::MAX))
+        }
+    };
+    if let Number::Int(value) = number {
+        // no integer type hold more than this, whether a smaller value fit the type it is
+        // given is told by the type checker
+        if value > u64::MAX as i128 {
+            return Err(format!(
+                "số `{}` quá lớn, không vừa với kiểu số nguyên nào",
+                chars[i..j].iter().collect::<String>()
+            ));
+        }
+    }
+    if let Some(c) = chars.get(j).filter(|c| is_word_char(**c)) {
+        let end = j + chars[j..].iter().take_while(|c| is_word_char(**c)).count();
+        return Err(format!(
+            "`{}` không phải là một số, `{}` không được viết liền sau số",
+            chars[i..end].iter().collect::<String>(),
+            c
+        ));
+    }
+    Ok((number, j))
+}
+
+fn skip_digits(chars: &[char], mut j: usize) -> usize {
+    while j < chars.len() && (chars[j].is_ascii_digit() || chars[j] == '_') {
+        j += 1;
+    }
+    j
+}
+
+/// the digits of `chars` without their `_`, a `_` must be between two digits
+fn digit_group(chars: &[char], raw: &str) -> Result<String, String> {
+    if chars.is_empty() {
+        return Err(format!("`{}` thiếu chữ số", raw));
+    }
+    let misplaced = chars.first() == Some(&'_')
+        || chars.last() == Some(&'_')
+        || chars.windows(2).any(|w| w == ['_', '_']);
+    if misplaced {
+        return Err(format!(
+            "`{}`: dấu `_` chỉ dùng để ngăn cách giữa hai chữ số",
+            raw
+        ));
+    }
+    Ok(chars.iter().filter(|c| **c != '_').collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_string_to_ast, test_pack, ExprKind, Stmt};
+
+    fn chars(source: &str) -> Vec<char> {
+        source.chars().collect()
+    }
+
+    /// the value of a literal that take the whole of `source`
+    fn string(source: &str) -> Result<String, String> {
+        let chars = chars(source);
+        read_string_literal(&chars, 0).map(|(value, end)| {
+            assert_eq!(end, chars.len(), "`{}` không được đọc hết", source);
+            value
+        })
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            string(r#""a\tb\nc\r\\\"\u{1EC7}\u{41}""#).unwrap(),
+            "a\tb\nc\r\\\"ệA"
+        );
+        assert_eq!(
+            string(r#""\q""#).unwrap_err(),
+            r#"kí tự thoát `\q` không hợp lệ, chỉ có `\n \t \r \\ \"` và `\u{...}`"#
+        );
+        assert_eq!(
+            string(r#""\u1EC7""#).unwrap_err(),
+            r"kí tự thoát `\u` phải có dạng `\u{1EC7}`"
+        );
+        assert_eq!(
+            string(r#""\u{110000}""#).unwrap_err(),
+            r"`\u{110000}` không phải là một kí tự Unicode"
+        );
+        assert_eq!(string("\"a\\").unwrap_err(), r"chuỗi kết thúc bằng `\`");
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(string(r#"r"C:\thư_mục\n""#).unwrap(), r"C:\thư_mục\n");
+        // the `r` of a name is not a raw string
+        assert_eq!(string_literal_end(&chars(r#"tr"a""#), 1), None);
+        assert_eq!(string_literal_end(&chars(r#"tr"a""#), 2), Some(5));
+    }
+
+    #[test]
+    fn triple_quoted_strings() {
+        assert_eq!(
+            string("\"\"\"\ndòng 1\n  \"dòng\" 2\n\"\"\"").unwrap(),
+            "dòng 1\n  \"dòng\" 2\n"
+        );
+        assert_eq!(string("r\"\"\"a\\n\nb\"\"\"").unwrap(), "a\\n\nb");
+        assert_eq!(
+            string("\"\"\"a\n").unwrap_err(),
+            "chuỗi `\"\"\"` chưa được đóng đến hết tệp"
+        );
+    }
+
+    #[test]
+    fn unclosed_strings() {
+        assert_eq!(
+            string("\"abc").unwrap_err(),
+            "chuỗi chưa được đóng bằng `\"`"
+        );
+        assert_eq!(
+            read_string_literal(&chars("\"abc\nin: 1"), 0).unwrap_err(),
+            "chuỗi chưa được đóng bằng `\"`, dùng `\"\"\"` cho chuỗi nhiều dòng"
+        );
+        // the end of an unclosed literal is the end of its line
+        assert_eq!(string_literal_end(&chars("\"a\\\"b\nc"), 0), Some(5));
+    }
+
+    #[test]
+    fn commas_and_quotes_in_arguments() {
+        let source = r#"in: "a, b", "c\"d", r"\", 1"#.to_string() + "\n";
+        let ast = parse_string_to_ast(source, &test_pack("vi")).unwrap();
+        let Stmt::Call { call, .. } = &ast[ast.body[0]] else {
+            panic!("không phải lệnh in")
+        };
+        let args = call
+            .args
+            .iter()
+            .map(|a| match &ast[*a].kind {
+                ExprKind::String(s) => s.clone(),
+                kind => format!("{:?}", kind),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(args, ["a, b", "c\"d", "\\", "Int(1)"]);
+    }
+
+    /// the number of a literal that take the whole of `source`
+    fn number(source: &str) -> Result<Number, String> {
+        let chars = chars(source);
+        read_number_literal(&chars, 0).map(|(value, end)| {
+            assert_eq!(end, chars.len(), "`{}` không được đọc hết", source);
+            value
+        })
+    }
+
+    #[test]
+    fn integer_literals() {
+        assert_eq!(number("0xFF"), Ok(Number::Int(255)));
+        assert_eq!(number("0X1f"), Ok(Number::Int(31)));
+        assert_eq!(number("0o17"), Ok(Number::Int(15)));
+        assert_eq!(number("0b1010_0101"), Ok(Number::Int(165)));
+        assert_eq!(number("1_000_000"), Ok(Number::Int(1_000_000)));
+        assert_eq!(
+            number("18446744073709551615"),
+            Ok(Number::Int(u64::MAX as i128))
+        );
+        assert_eq!(
+            number("0xFFFF_FFFF_FFFF_FFFF_F"),
+            Err(
+                "số `0xFFFF_FFFF_FFFF_FFFF_F` quá lớn, không vừa với kiểu số nguyên nào"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            number("1234567890123456789012345678901234567890"),
+            Err("số `1234567890123456789012345678901234567890` quá lớn, không vừa với kiểu số nguyên nào".to_string())
+        );
+        // `1..3` is a range, the dots are not read
+        assert_eq!(
+            read_number_literal(&chars("1..3"), 0),
+            Ok((Number::Int(1), 1))
+        );
+    }
+
+    #[test]
+    fn real_and_decimal_literals() {
+        assert_eq!(number("2.5"), Ok(Number::Float(2.5)));
+        assert_eq!(number("1e-3"), Ok(Number::Float(0.001)));
+        assert_eq!(number("6.02E+23"), Ok(Number::Float(6.02e23)));
+        assert_eq!(number("1_000.000_5"), Ok(Number::Float(1000.0005)));
+        assert_eq!(number("15_000.5đ"), Ok(Number::Decimal(150_005_000)));
+        assert_eq!(number("3đ"), Ok(Number::Decimal(30_000)));
+        assert_eq!(
+            number("1e5đ"),
+            Err("số thập phân `1e5đ` không viết được với số mũ".to_string())
+        );
+        assert_eq!(
+            number("0.00001đ"),
+            Err(
+                "số thập phân `0.00001đ` quá lớn hoặc có nhiều hơn 4 chữ số sau dấu chấm"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            number("1e400"),
+            Err("số `1e400` quá lớn, không vừa với số thực".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        assert_eq!(number("0x"), Err("`0x` thiếu chữ số".to_string()));
+        assert_eq!(
+            number("0b102"),
+            Err("`0b102` không phải là một số hệ 2".to_string())
+        );
+        assert_eq!(
+            number("1__000"),
+            Err("`1__000`: dấu `_` chỉ dùng để ngăn cách giữa hai chữ số".to_string())
+        );
+        assert_eq!(
+            number("1_000_"),
+            Err("`1_000_`: dấu `_` chỉ dùng để ngăn cách giữa hai chữ số".to_string())
+        );
+        assert_eq!(
+            number("12abc"),
+            Err("`12abc` không phải là một số, `a` không được viết liền sau số".to_string())
+        );
+        assert_eq!(
+            number("2đồng"),
+            Err("`2đồng` không phải là một số, `đ` không được viết liền sau số".to_string())
+        );
+    }
+}
@@ -1,12 +1,61 @@
+mod accent;
+mod ast;
+mod builtin;
+mod config;
+mod expr;
+mod literal;
+mod translate;
+mod visit;
 use std::collections::BTreeMap;
 
 use lazy_static::lazy_static;
 use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
+
+pub use accent::{resolve_accents, strip_accents};
+pub use ast::*;
+pub use builtin::BuiltinFunction;
+pub use config::load_config;
+pub use translate::{source_pack, translate};
+pub use visit::*;
 
 #[derive(Debug, Default)]
 pub struct KeywordConfig {
     pub default_function: BTreeMap<String, DefaultFunctionType>,
     pub variable_keyword: Vec<String>,
+    pub constant_keyword: Vec<String>,
+    pub type_keyword: BTreeMap<String, ValueType>,
+    /// the `[operators]` of the config, `"cộng" = "+"`
+    pub operators: BTreeMap<String, String>,
+    /// `accent_insensitive = true` in `[options]`, keywords match when they are typed
+    /// without accents
+    pub accent_insensitive: bool,
+    /// the keywords opening a block, `"khối" = "block"`
+    pub block_keyword: Vec<String>,
+    /// the keywords closing a block, `"hết" = "end"`
+    pub end_keyword: Vec<String>,
+    /// `blocks = "keyword"` or `blocks = "indent"` in `[options]`
+    pub block_style: BlockStyle,
+}
+
+/// how the end of a block is found
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStyle {
+    /// the block start with a line `khối` and stop at a line `hết`
+    #[default]
+    Keyword,
+    /// the block start with a line `khối:` and hold the lines indented under it, like Python
+    Indent,
+}
+
+impl BlockStyle {
+    pub fn from_config_type(data: &str) -> Option<Self> {
+        match data.trim() {
+            "keyword" => Some(Self::Keyword),
+            "indent" => Some(Self::Indent),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -16,335 +65,843 @@ pub struct DefaultFunctionType {
 }
 
 lazy_static! {
-    static ref CALL_FUNCTION_REGEX: Regex = Regex::new(r"(\w+)\s?(\((.+)?\))").unwrap();
-    static ref SPECIAL_CALL_FUNCTION_REGEX: Regex = Regex::new(r"(\w+)\s?:\s?(.+)?").unwrap();
-    static ref SET_VARIABLE_REGEX: Regex = Regex::new(r"(\w+)\s?=\s?(.+)").unwrap();
+    // `(?s)` so the value can be a `"""` string of many lines
+    static ref CALL_FUNCTION_REGEX: Regex = Regex::new(r"(?s)^(\w+)\s?(\((.+)?\))").unwrap();
+    static ref SPECIAL_CALL_FUNCTION_REGEX: Regex =
+        Regex::new(r"(?s)^(\w+)\s?:\s?(.+)?").unwrap();
+    static ref SET_VARIABLE_REGEX: Regex = Regex::new(r"(?s)^(\w+)\s?=\s?(.+)").unwrap();
     static ref OPS_LIST: Vec<String> = vec![
         "+".to_string(),
         "-".to_string(),
         "*".to_string(),
         "/".to_string(),
+        "%".to_string(),
+        "<".to_string(),
+        "<=".to_string(),
+        ">".to_string(),
+        ">=".to_string(),
+        "==".to_string(),
+        "!=".to_string(),
     ];
 }
 
-pub fn parse_config(config: String) -> KeywordConfig {
-    let mut result = KeywordConfig::default();
-    for s in config.split("\n") {
-        if s.trim().is_empty() {
-            continue;
-        }
-        // sample value is in=print
-        let tmp = s.split("=").collect::<Vec<&str>>();
-        result.default_function.insert(
-            tmp[0].to_string(),
-            DefaultFunctionType {
-                name: tmp[0].to_string(),
-                r#type: tmp[1].to_string(),
-            },
-        );
-        if tmp[1] == "variable" {
-            result.variable_keyword.push(tmp[0].to_string());
-        }
-    }
-    return result;
-}
-pub type Ast = Vec<AstNode>;
-
-#[derive(Debug, Default, Clone)]
-pub struct AstNode {
-    pub op: Operation,
-    pub left: Ast,
-    pub right: Ast,
-    pub raw: String,
-}
-
-#[derive(Debug, Default, Clone)]
-pub enum AstNodeValue {
-    String(String),
-    Number(f64),
-    #[default]
-    None,
-    Bool(bool),
-    Variable(String),
-    Operation(Box<AstNode>),
+/// keyboards write `à` as one character or as `a` followed by a combining mark, both are
+/// turned into the composed form (NFC) so the same word is always the same symbol
+pub fn normalize_source(data: &str) -> String {
+    data.nfc().collect()
 }
 
-#[derive(Debug, Default, Clone)]
-pub enum Operation {
-    #[default]
-    None,
-    Ident(IdentType),
-    Value(ValueMetaType),
-    Call,
-    NewVariable,
-    SetVariable,
-    IntOperation(IntOperationType),
-}
-
-#[derive(Debug, Default, Clone)]
-pub enum IntOperationType {
-    Plus,
-    Minus,
-    Times,
-    Divide,
-    #[default]
-    None,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// signed 64 bit, the type of every integer literal
+    Int,
+    Int8,
+    Int16,
+    Int32,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float,
+    /// `thập_phân`, exact number with 4 digits after the dot stored as an i64 counting
+    /// 1/10000, computed by the runtime library
+    Decimal,
+    String,
+    /// `kí_tự`, one character as the user see it (a grapheme cluster), stored like a string
+    Char,
+    /// `danh_sách`, a list of strings owned by the runtime library
+    List,
+    Bool,
 }
 
-impl IntOperationType {
-    pub fn from_string_symbol(data: &str) -> Self {
+impl ValueType {
+    pub fn from_config_type(data: &str) -> Option<Self> {
         match data.trim() {
-            "+" => Self::Plus,
-            "-" => Self::Minus,
-            "*" => Self::Times,
-            "/" => Self::Divide,
-            _ => {
-                panic!("{} cái phép tính beep gì đây", data)
-            }
+            "int" | "i64" => Some(Self::Int),
+            "i8" => Some(Self::Int8),
+            "i16" => Some(Self::Int16),
+            "i32" => Some(Self::Int32),
+            "u8" => Some(Self::UInt8),
+            "u16" => Some(Self::UInt16),
+            "u32" => Some(Self::UInt32),
+            "u64" => Some(Self::UInt64),
+            "float" => Some(Self::Float),
+            "decimal" => Some(Self::Decimal),
+            "string" => Some(Self::String),
+            "char" => Some(Self::Char),
+            "list" => Some(Self::List),
+            "bool" => Some(Self::Bool),
+            _ => None,
+        }
+    }
+    pub fn is_number(&self) -> bool {
+        self.is_int() || *self == Self::Float || *self == Self::Decimal
+    }
+    pub fn is_int(&self) -> bool {
+        self.int_info().is_some()
+    }
+    /// bit width and signedness of an integer type
+    pub fn int_info(&self) -> Option<(u32, bool)> {
+        match self {
+            ValueType::Int => Some((64, true)),
+            ValueType::Int8 => Some((8, true)),
+            ValueType::Int16 => Some((16, true)),
+            ValueType::Int32 => Some((32, true)),
+            ValueType::UInt8 => Some((8, false)),
+            ValueType::UInt16 => Some((16, false)),
+            ValueType::UInt32 => Some((32, false)),
+            ValueType::UInt64 => Some((64, false)),
+            _ => None,
         }
     }
-    pub fn to_symbol_string(&self) -> String {
+    /// smallest and biggest value an integer type can hold
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        let (bits, signed) = self.int_info()?;
+        Some(if signed {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        })
+    }
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            IntOperationType::Plus => "+",
-            IntOperationType::Minus => "-",
-            IntOperationType::Times => "*",
-            IntOperationType::Divide => "/",
+            ValueType::Int => write!(f, "số nguyên"),
+            ValueType::Float => write!(f, "số thực"),
+            ValueType::Decimal => write!(f, "thập phân"),
+            ValueType::String => write!(f, "chuỗi"),
+            ValueType::Char => write!(f, "kí tự"),
+            ValueType::List => write!(f, "danh sách"),
+            ValueType::Bool => write!(f, "logic"),
             _ => {
-                panic!("cái phép tính beep gì dậy trời")
+                let (bits, signed) = self.int_info().unwrap();
+                if signed {
+                    write!(f, "số nguyên {} bit", bits)
+                } else {
+                    write!(f, "số tự nhiên {} bit", bits)
+                }
             }
         }
-        .to_string()
     }
 }
 
-impl Operation {
-    pub fn get_ident_value(&self) -> Option<IdentType> {
-        return if let Operation::Ident(v) = self {
-            Some(v.clone())
-        } else {
-            None
-        };
-    }
-    pub fn get_value_value(&self) -> Option<ValueMetaType> {
-        match self {
-            Operation::Value(v) => Some(v.clone()),
+/// how `làm_tròn` and its siblings round a `thập_phân`, the number is the one the runtime
+/// library expect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    HalfUp = 0,
+    HalfEven = 1,
+    Ceiling = 2,
+    Floor = 3,
+}
+
+impl RoundingMode {
+    pub fn from_config_type(data: &str) -> Option<Self> {
+        match data.trim() {
+            "round" => Some(Self::HalfUp),
+            "round_half_even" => Some(Self::HalfEven),
+            "round_ceiling" => Some(Self::Ceiling),
+            "round_floor" => Some(Self::Floor),
             _ => None,
         }
     }
-    // pub fn get_int_operation(&self) -> Option<IntOperationType> {
-    //     match self {
-    //         Self::IntOperation(v) => Some(v.clone()),
-    //         _ => None,
-    //     }
-    // }
 }
 
-#[derive(Debug, Default, Clone)]
-pub enum ValueMetaType {
-    #[default]
-    None,
-    FunctionArg(AstNodeValue),
-    VariableValue(AstNodeValue),
-    MathValue(AstNodeValue),
-}
+pub const DECIMAL_SCALE: i64 = 10_000;
+pub const DECIMAL_DIGITS: usize = 4;
 
-impl ValueMetaType {
-    pub fn get_function_args(&self) -> Option<AstNodeValue> {
-        return if let ValueMetaType::FunctionArg(fa) = self {
-            Some(fa.clone())
-        } else {
-            None
-        };
+/// read `12.5` as the `thập_phân` 125000, `None` when the text has more than 4 digits
+/// after the dot or does not fit
+pub fn parse_decimal_literal(data: &str) -> Option<i64> {
+    let data = data.trim();
+    let (negative, data) = match data.strip_prefix("-") {
+        Some(d) => (true, d),
+        None => (false, data),
+    };
+    let (integer, fraction) = data.split_once(".").unwrap_or((data, ""));
+    if integer.is_empty()
+        || fraction.len() > DECIMAL_DIGITS
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
     }
-    pub fn get_variable_value(&self) -> Option<AstNodeValue> {
-        return if let ValueMetaType::VariableValue(var_val) = self {
-            Some(var_val.clone())
-        } else {
-            None
-        };
+    let fraction = format!("{:0<width$}", fraction, width = DECIMAL_DIGITS);
+    let value = integer
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(DECIMAL_SCALE)?
+        .checked_add(fraction.parse::<i64>().ok()?)?;
+    Some(if negative { -value } else { value })
+}
+
+/// the text `in` print for a `thập_phân`, same as `vipl_decimal_format` in the runtime
+pub fn format_decimal(value: i64) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let integer = value.unsigned_abs() / DECIMAL_SCALE as u64;
+    let fraction = value.unsigned_abs() % DECIMAL_SCALE as u64;
+    if fraction == 0 {
+        format!("{}{}", sign, integer)
+    } else {
+        let fraction = format!("{:0width$}", fraction, width = DECIMAL_DIGITS);
+        format!("{}{}.{}", sign, integer, fraction.trim_end_matches('0'))
     }
-    pub fn get_math_value(&self) -> Option<AstNodeValue> {
-        return if let ValueMetaType::MathValue(var_val) = self {
-            Some(var_val.clone())
-        } else {
-            None
-        };
+}
+
+/// position of a node in the source file, `line` start from 1 and columns count chars
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// the source line of the span with `^` under it like `rustc` does
+    pub fn show(&self, source: &str) -> String {
+        let line = source
+            .split("\n")
+            .nth(self.line.saturating_sub(1))
+            .unwrap_or_default();
+        format!(
+            "  | {}\n  | {}{}",
+            line,
+            " ".repeat(self.start),
+            "^".repeat((self.end - self.start).max(1)),
+        )
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct IdentType {
-    pub data: String,
-    pub metadata: IdentMetaDataType,
+pub struct ParseError {
+    pub span: Span,
+    pub message: String,
 }
 
-impl IdentType {
-    pub fn get_function_name(&self) -> Option<String> {
-        match self.metadata {
-            IdentMetaDataType::FunctionName => Some(self.data.clone()),
-            _ => None,
-        }
+impl ParseError {
+    pub fn new(span: Span, message: String) -> Self {
+        Self { span, message }
     }
-    pub fn get_variable_name(&self) -> Option<String> {
-        match self.metadata {
-            IdentMetaDataType::VariableName => Some(self.data.clone()),
-            _ => None,
+    pub fn report(&self, source: &str) -> String {
+        format!(
+            "lỗi cú pháp ở dòng {}:{}: {}\n{}",
+            self.span.line,
+            self.span.start + 1,
+            self.message,
+            self.span.show(source)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ContextType {
+    /// the word operators of the config split in words, the longest first so
+    /// `chia lấy dư` win over `chia`
+    pub operators: Vec<(Vec<String>, BinaryOp)>,
+    /// `var x: kiểu = giá_trị` with the variable and constant keywords of the config,
+    /// built once for the whole file
+    pub declaration: Regex,
+    /// the mistakes found in the statement being parsed, reported with its span
+    pub errors: Vec<String>,
+}
+
+impl ContextType {
+    pub fn new(config: &KeywordConfig) -> Self {
+        let mut operators = config
+            .operators
+            .iter()
+            .map(|(words, symbol)| {
+                (
+                    words
+                        .split_whitespace()
+                        .map(String::from)
+                        .collect::<Vec<_>>(),
+                    // the config only accept the symbols of `BinaryOp`
+                    BinaryOp::from_symbol(symbol).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        operators.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        let declaration = Regex::new(&format!(
+            r"(?s)^({})\s+(\w+)\s*(:\s*(\w+))?(\s+)?(=)?(.+)?",
+            config
+                .variable_keyword
+                .iter()
+                .chain(config.constant_keyword.iter())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("|")
+        ))
+        .unwrap();
+        Self {
+            operators,
+            declaration,
+            errors: vec![],
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum IdentMetaDataType {
-    FunctionName,
-    VariableName,
+/// a block being read, the root of the program is the first one
+struct OpenBlock {
+    /// the line opening the block, `khối` or `khối:`
+    keyword: String,
+    span: Span,
+    body: Vec<StmtId>,
+    /// the indentation of the lines of the block, `None` until its first line is read
+    indent: Option<String>,
 }
 
-#[derive(Debug, Default)]
-pub struct ContextType {
-    pub variable: std::collections::BTreeMap<String, AstNodeValue>,
+/// one statement of the source, it can be a part of a line or span many lines
+struct SourceStatement {
+    /// the span is on the first line of the statement
+    span: Span,
+    /// the indentation of the line the statement start on
+    indent: String,
+    /// the lines of the statement joined with a space, without their comments
+    text: String,
+    /// the `##` lines right before the statement and the span of the first one
+    doc: Option<(Span, String)>,
 }
 
-pub fn parse_string_to_ast(data: String, config: &KeywordConfig) -> Ast {
-    let mut result = Ast::default();
-    let mut context = ContextType::default();
-    for line in data.split("\n") {
-        if line.starts_with("#") {
+/// cut the source in statements: `;` separate the statements of a line and a statement
+/// continue on the next line while a `(` or `[` is open or after a trailing operator or
+/// comma:
+///
+/// ```text
+/// ## the price with the tax
+/// var tổng = giá +
+///     thuế # in VND
+/// in: "tổng: ", #[ a block comment
+///    that go on ]# tổng; in: "\n"
+/// ```
+///
+/// a `#` inside a string literal is not a comment and a `"""` string keep its lines
+fn split_statements(
+    data: &str,
+    config: &KeywordConfig,
+    errors: &mut Vec<ParseError>,
+) -> Vec<SourceStatement> {
+    let chars = data.chars().collect::<Vec<_>>();
+    let mut result = vec![];
+    // the statement being read
+    let mut current: Option<SourceStatement> = None;
+    let mut doc: Option<(Span, String)> = None;
+    let mut depth = 0usize;
+    // the `#[` of the block comments still open, they can be nested
+    let mut comments = vec![];
+    // the blanks after a line break joining two lines of a statement are skipped
+    let mut joined = false;
+    let mut line = 0;
+    let mut line_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let line_end = || {
+            chars[i..]
+                .iter()
+                .position(|c| *c == '\n')
+                .map_or(chars.len(), |p| i + p)
+        };
+        let span = |start: usize, end: usize| Span {
+            line: line + 1,
+            start: start - line_start,
+            end: end - line_start,
+        };
+        if c == '\n' {
+            if let Some(mut statement) = current.take() {
+                if comments.is_empty()
+                    && (depth > 0 || continue_on_next_line(statement.text.trim_end(), config))
+                {
+                    statement.text.truncate(statement.text.trim_end().len());
+                    statement.text.push(' ');
+                    joined = true;
+                    current = Some(statement);
+                } else if !comments.is_empty() {
+                    current = Some(statement);
+                } else {
+                    result.push(finish_statement(statement));
+                }
+            }
+            line += 1;
+            line_start = i + 1;
+            i += 1;
+            continue;
+        }
+        if !comments.is_empty() {
+            if c == '#' && next == Some('[') {
+                comments.push(span(i, i + 2));
+                i += 1;
+            } else if c == ']' && next == Some('#') {
+                comments.pop();
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        if i == line_start && current.is_none() {
+            let line_text = chars[i..line_end()].iter().collect::<String>();
+            if let Some(text) = line_text.trim_start().strip_prefix("##") {
+                let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+                match &mut doc {
+                    Some((_, doc)) => {
+                        doc.push('\n');
+                        doc.push_str(text);
+                    }
+                    None => {
+                        let start =
+                            i + line_text.chars().count() - line_text.trim_start().chars().count();
+                        doc = Some((
+                            span(start, start + line_text.trim().chars().count()),
+                            text.to_string(),
+                        ));
+                    }
+                }
+                i = line_end();
+                continue;
+            }
+        }
+        if c == '#' {
+            if next == Some('[') {
+                comments.push(span(i, i + 2));
+                if let Some(statement) = &mut current {
+                    statement.text.push(' ');
+                }
+                i += 2;
+            } else {
+                i = line_end();
+            }
             continue;
         }
-        if line.trim().len() <= 0 {
+        if c.is_whitespace() && (current.is_none() || joined) {
+            i += 1;
             continue;
         }
-        result.push(parse_string(line.trim(), config, &mut context))
+        joined = false;
+        if c == ';' && depth == 0 {
+            if let Some(statement) = current.take() {
+                result.push(finish_statement(statement));
+            }
+            i += 1;
+            continue;
+        }
+        let statement = current.get_or_insert_with(|| SourceStatement {
+            span: span(i, i + 1),
+            indent: chars[line_start..]
+                .iter()
+                .take_while(|c| **c != '\n' && c.is_whitespace())
+                .collect(),
+            text: String::new(),
+            doc: doc.take(),
+        });
+        let end = literal::string_literal_end(&chars, i).unwrap_or(i + 1);
+        for c in &chars[i..end] {
+            statement.text.push(*c);
+            if *c == '\n' {
+                line += 1;
+            }
+        }
+        if statement.span.line == line + 1 && !c.is_whitespace() {
+            statement.span.end = end - line_start;
+        }
+        if let Some(p) = chars[i..end].iter().rposition(|c| *c == '\n') {
+            line_start = i + p + 1;
+        }
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i = end;
+    }
+    if let Some(statement) = current {
+        if depth > 0 {
+            errors.push(ParseError::new(
+                statement.span,
+                "dấu ngoặc mở ở đây chưa được đóng đến hết tệp".to_string(),
+            ));
+        }
+        result.push(finish_statement(statement));
+    }
+    if let Some(span) = comments.first() {
+        errors.push(ParseError::new(
+            *span,
+            "chú thích `#[` chưa được đóng bằng `]#`".to_string(),
+        ));
     }
-    return result;
+    if let Some((span, _)) = doc {
+        errors.push(misplaced_doc(span));
+    }
+    result
 }
 
-fn parse_string(data: &str, config: &KeywordConfig, context: &mut ContextType) -> AstNode {
-    let mut result = AstNode::default();
+fn finish_statement(mut statement: SourceStatement) -> SourceStatement {
+    statement.text.truncate(statement.text.trim_end().len());
+    statement
+}
 
-    result.raw = data.to_string();
-    let new_variable_long_style_regex = regex::Regex::new(&format!(
-        r"({})\s(\w+)(\s+)?(=)?(.+)?",
-        config.variable_keyword.join("|")
-    ))
-    .unwrap();
+fn misplaced_doc(span: Span) -> ParseError {
+    ParseError::new(
+        span,
+        "chú thích `##` phải đứng ngay trước một khai báo `var` hoặc `hằng`".to_string(),
+    )
+}
 
-    if let Some(p) = CALL_FUNCTION_REGEX.captures(data) {
-        result = parse_call_function_syntax(
-            p.get(1).unwrap().as_str(),
-            p.get(2).unwrap().as_str(),
-            context,
-        );
-    } else if let Some(p) = SPECIAL_CALL_FUNCTION_REGEX.captures(data) {
-        result = parse_call_function_syntax(
-            p.get(1).unwrap().as_str(),
-            p.get(2).unwrap().as_str(),
-            context,
-        );
-    } else if let Some(p) = new_variable_long_style_regex.captures(data) {
-        result.op = Operation::NewVariable;
-        result
-            .left
-            .push(parse_variable_name(p.get(2).unwrap().as_str()));
-
-        if let Some(data) = p.get(5) {
-            result
-                .right
-                .push(parse_variable_value(data.as_str(), context));
-        }
-        context.variable.insert(
-            p.get(2).unwrap().as_str().to_string(),
-            if result.right.len() == 0 {
-                AstNodeValue::default()
+/// `a +`, `in: a,` and `a cộng` are not finished
+fn continue_on_next_line(text: &str, config: &KeywordConfig) -> bool {
+    OPS_LIST.iter().any(|o| text.ends_with(o.as_str()))
+        || text.ends_with(',')
+        || config.operators.keys().any(|o| {
+            text.strip_suffix(o.as_str())
+                .is_some_and(|rest| rest.ends_with(char::is_whitespace))
+        })
+}
+
+pub fn parse_string_to_ast(data: String, config: &KeywordConfig) -> Result<Ast, Vec<ParseError>> {
+    let mut errors = vec![];
+    let mut context = ContextType::new(config);
+    let mut ast = Ast::default();
+    let mut blocks = vec![OpenBlock {
+        keyword: String::new(),
+        span: Span::default(),
+        body: vec![],
+        indent: Some(String::new()),
+    }];
+    for statement in split_statements(&data, config, &mut errors) {
+        let span = statement.span;
+        if config.block_style == BlockStyle::Indent {
+            close_indented_blocks(&statement.indent, span, &mut blocks, &mut ast, &mut errors);
+        }
+        let data = statement.text.as_str();
+        // `khối` opens a block in the keyword style and `khối:` in the indent style
+        let is_block = |data: &str| config.block_keyword.iter().any(|k| k == data);
+        let with_colon = data.strip_suffix(':').is_some_and(|d| is_block(d.trim()));
+        let is_end = config.end_keyword.iter().any(|k| k == data);
+        if let (Some((doc_span, _)), true) =
+            (&statement.doc, is_block(data) || with_colon || is_end)
+        {
+            errors.push(misplaced_doc(*doc_span));
+        }
+        if is_block(data) || with_colon {
+            match (config.block_style, with_colon) {
+                (BlockStyle::Keyword, true) => {
+                    errors.push(ParseError::new(
+                        span,
+                        format!(
+                            "khối kết thúc bằng từ khoá không có `:`, viết `{}` rồi đóng bằng `{}`",
+                            data.trim_end_matches(':').trim(),
+                            config.end_keyword.first().map_or("hết", String::as_str)
+                        ),
+                    ));
+                    continue;
+                }
+                (BlockStyle::Indent, false) => errors.push(ParseError::new(
+                    span,
+                    format!("khối thụt lề phải có `:` ở cuối: `{}:`", data),
+                )),
+                _ => {}
+            }
+            open_block(data, span, &mut blocks);
+            continue;
+        }
+        if is_end {
+            if config.block_style == BlockStyle::Indent {
+                errors.push(ParseError::new(
+                    span,
+                    format!(
+                        "`{}` không dùng được khi khối được đánh dấu bằng thụt lề (`blocks = \"indent\"`)",
+                        data
+                    ),
+                ));
+            } else if blocks.len() == 1 {
+                errors.push(ParseError::new(
+                    span,
+                    format!("`{}` không đóng khối nào, không có khối nào đang mở", data),
+                ));
             } else {
-                result.right[0]
-                    .op
-                    .get_value_value()
-                    .unwrap()
-                    .get_variable_value()
-                    .unwrap()
-            },
-        );
-    } else if let Some(p) = SET_VARIABLE_REGEX.captures(data) {
-        result.op = Operation::SetVariable;
-        result
-            .left
-            .push(parse_variable_name(p.get(1).unwrap().as_str()));
-        result
-            .right
-            .push(parse_variable_value(p.get(2).unwrap().as_str(), context))
-    }
-    result.raw = data.to_string();
-    return result;
-}
-
-fn parse_variable_value(data: &str, context: &mut ContextType) -> AstNode {
-    let mut result = AstNode::default();
-    result.op = Operation::Value(ValueMetaType::VariableValue(AstNodeValue::from_string(
-        data,
-        &context.variable,
-    )));
-    result.raw = data.to_string();
-    return result;
-}
-
-fn parse_call_function_syntax(name: &str, data: &str, context: &mut ContextType) -> AstNode {
-    let mut result = AstNode::default();
-    result.op = Operation::Call;
-    result.left.push(parse_function_name(name));
-    for v in parse_function_args(data, context) {
-        result.right.push(v);
+                close_block(&mut blocks, &mut ast);
+            }
+            continue;
+        }
+        let offset = ast.push_text(data).start;
+        let node = parse_string(data, offset, span, config, &mut context, &mut ast);
+        for message in context.errors.drain(..) {
+            errors.push(ParseError::new(span, message));
+        }
+        let mut node = match node {
+            Some(node) => node,
+            None => continue,
+        };
+        if let Some((doc_span, text)) = statement.doc {
+            match &mut node {
+                Stmt::Let { doc, .. } => *doc = Some(text),
+                _ => errors.push(misplaced_doc(doc_span)),
+            }
+        }
+        let id = ast.push_stmt(node);
+        blocks.last_mut().unwrap().body.push(id);
     }
-    result
+    while blocks.len() > 1 {
+        if config.block_style == BlockStyle::Keyword {
+            let block = blocks.last().unwrap();
+            errors.push(ParseError::new(
+                block.span,
+                format!(
+                    "khối `{}` chưa được đóng, thiếu `{}`",
+                    block.keyword,
+                    config.end_keyword.first().map_or("hết", String::as_str)
+                ),
+            ));
+        } else if blocks.last().unwrap().indent.is_none() {
+            let block = blocks.last().unwrap();
+            errors.push(ParseError::new(
+                block.span,
+                format!("khối `{}` không có dòng nào", block.keyword),
+            ));
+        }
+        close_block(&mut blocks, &mut ast);
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    ast.body = blocks.pop().unwrap().body;
+    Ok(ast)
 }
 
-fn parse_function_name(name: &str) -> AstNode {
-    let mut node = AstNode::default();
-    node.op = Operation::Ident(IdentType {
-        data: name.to_string(),
-        metadata: IdentMetaDataType::FunctionName,
+fn open_block(data: &str, span: Span, blocks: &mut Vec<OpenBlock>) {
+    blocks.push(OpenBlock {
+        keyword: data.to_string(),
+        span,
+        body: vec![],
+        indent: None,
     });
-    node.raw = name.to_string();
-    return node;
-}
-fn parse_variable_name(name: &str) -> AstNode {
-    let mut node = AstNode::default();
-    node.op = Operation::Ident(IdentType {
-        data: name.to_string(),
-        metadata: IdentMetaDataType::VariableName,
+}
+
+fn close_block(blocks: &mut Vec<OpenBlock>, ast: &mut Ast) {
+    let block = blocks.pop().unwrap();
+    let id = ast.push_stmt(Stmt::Block {
+        body: block.body,
+        span: block.span,
     });
-    node.raw = name.to_string();
-    return node;
+    blocks.last_mut().unwrap().body.push(id);
 }
 
-fn parse_function_args(raw_args: &str, context: &mut ContextType) -> Ast {
-    let mut ast = Ast::default();
-    if raw_args.starts_with("(")
-        && raw_args.ends_with(")")
-        && !check_string_is_math_operation(raw_args)
+/// with `blocks = "indent"` a line less indented than the block close it. The indentation
+/// must be the one of an open block, written with the same tabs and spaces
+fn close_indented_blocks(
+    indent: &str,
+    span: Span,
+    blocks: &mut Vec<OpenBlock>,
+    ast: &mut Ast,
+    errors: &mut Vec<ParseError>,
+) {
+    let parent_indent = |blocks: &[OpenBlock]| {
+        blocks[..blocks.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|b| b.indent.clone())
+            .unwrap_or_default()
+    };
+    // the first line of a block choose its indentation
+    if blocks.last().unwrap().indent.is_none() {
+        let parent = parent_indent(blocks);
+        if indent.len() > parent.len() && indent.starts_with(&parent) {
+            blocks.last_mut().unwrap().indent = Some(indent.to_string());
+            return;
+        }
+        let block = blocks.last().unwrap();
+        errors.push(ParseError::new(
+            block.span,
+            format!(
+                "khối `{}` không có dòng nào, dòng {} phải được thụt vào",
+                block.keyword, span.line
+            ),
+        ));
+        close_block(blocks, ast);
+    }
+    let mut closed = false;
+    loop {
+        let current = blocks.last().unwrap().indent.clone().unwrap();
+        if indent == current {
+            return;
+        }
+        if blocks.len() > 1 && current.starts_with(indent) {
+            close_block(blocks, ast);
+            closed = true;
+            continue;
+        }
+        let message = if !current.is_empty()
+            && (indent.contains('\t') != current.contains('\t')
+                || indent.contains(' ') != current.contains(' '))
+        {
+            "thụt lề trộn lẫn tab và dấu cách, khác với các dòng khác của khối"
+        } else if closed || !indent.starts_with(&current) {
+            "thụt lề không khớp với khối nào đang mở"
+        } else {
+            "dòng này thụt vào nhưng dòng trên không mở khối nào"
+        };
+        errors.push(ParseError::new(span, message.to_string()));
+        return;
+    }
+}
+
+/// `None` when the statement can not be read, the reason is in `context.errors`. The
+/// statement text start at `offset` in `ast`, its expressions point into it
+fn parse_string(
+    data: &str,
+    offset: usize,
+    span: Span,
+    config: &KeywordConfig,
+    context: &mut ContextType,
+    ast: &mut Ast,
+) -> Option<Stmt> {
+    if let Some(p) = context.declaration.captures(data) {
+        let name = p.get(2).unwrap().as_str().to_string();
+        let constant = config
+            .constant_keyword
+            .contains(&p.get(1).unwrap().as_str().to_string());
+        let annotation = p.get(4).map(|t| t.as_str().to_string());
+        let value = match p.get(7) {
+            Some(data) => Some(parse_value(
+                data.as_str(),
+                offset + data.start(),
+                context,
+                ast,
+            )?),
+            None if constant => {
+                context.errors.push(format!(
+                    "hằng `{}` phải được gán giá trị ngay khi khai báo",
+                    name
+                ));
+                return None;
+            }
+            None => None,
+        };
+        Some(Stmt::Let {
+            name,
+            constant,
+            annotation,
+            value,
+            doc: None,
+            value_type: None,
+            span,
+        })
+    } else if let Some(p) = CALL_FUNCTION_REGEX
+        .captures(data)
+        .or_else(|| SPECIAL_CALL_FUNCTION_REGEX.captures(data))
     {
+        let args = p.get(2).unwrap();
+        Some(Stmt::Call {
+            call: Call {
+                function: p.get(1).unwrap().as_str().to_string(),
+                args: parse_function_args(args.as_str(), offset + args.start(), context, ast)?,
+            },
+            span,
+        })
+    } else if let Some(p) = SET_VARIABLE_REGEX.captures(data) {
+        let value = p.get(2).unwrap();
+        Some(Stmt::Assign {
+            name: p.get(1).unwrap().as_str().to_string(),
+            value: parse_value(value.as_str(), offset + value.start(), context, ast)?,
+            value_type: None,
+            span,
+        })
+    } else {
+        context
+            .errors
+            .push(format!("không hiểu câu lệnh `{}`", data));
+        None
+    }
+}
+
+/// every argument is read even after a wrong one so all of them are reported
+fn parse_function_args(
+    raw_args: &str,
+    offset: usize,
+    context: &mut ContextType,
+    ast: &mut Ast,
+) -> Option<Vec<ExprId>> {
+    let args = if is_wrapped_in_parentheses(raw_args) {
         let content = remove_first_and_last(raw_args);
-        for raw in content.split(",") {
-            let mut node = AstNode::default();
-            node.op = Operation::Value(ValueMetaType::FunctionArg(AstNodeValue::from_string(
-                raw,
-                &context.variable,
-            )));
-            node.raw = raw.to_string();
-            ast.push(node.clone());
+        // `in()` has no argument
+        if content.trim().is_empty() {
+            vec![]
+        } else {
+            // skip the `(`
+            split_args(content)
+                .into_iter()
+                .map(|(start, arg)| (start + 1, arg))
+                .collect()
         }
     } else {
-        for raw in raw_args.split(",") {
-            let mut node = AstNode::default();
-            node.op = Operation::Value(ValueMetaType::FunctionArg(AstNodeValue::from_string(
-                raw,
-                &context.variable,
-            )));
-            node.raw = raw.to_string();
-            ast.push(node.clone());
+        split_args(raw_args)
+    };
+    args.into_iter()
+        .map(|(start, raw)| parse_value(raw, offset + start, context, ast))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect()
+}
+/// split on the commas that are not inside parentheses or a string,
+/// `in: làm_tròn(x, 2), "a, b"` has two arguments. Each one come with its byte position
+fn split_args(data: &str) -> Vec<(usize, &str)> {
+    let chars = data.chars().collect::<Vec<_>>();
+    let offsets = data.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(end) = literal::string_literal_end(&chars, i) {
+            i = end;
+            continue;
+        }
+        match chars[i] {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push((start, &data[start..offsets[i]]));
+                start = offsets[i] + 1;
+            }
+            _ => {}
         }
+        i += 1;
     }
-    return ast;
+    result.push((start, &data[start..]));
+    result
+}
+/// `(x - 1, "a")` is the argument list of `in(...)` but `(a) + (b)` is one value, the
+/// `(` at the start must be closed by the `)` at the end. The parentheses inside a string
+/// are not counted
+fn is_wrapped_in_parentheses(data: &str) -> bool {
+    let chars = data.chars().collect::<Vec<_>>();
+    if chars.first() != Some(&'(') {
+        return false;
+    }
+    let mut depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(end) = literal::string_literal_end(&chars, i) {
+            i = end;
+            continue;
+        }
+        match chars[i] {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i == chars.len() - 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    false
 }
 fn remove_first_and_last(value: &str) -> &str {
     let mut chars = value.chars();
@@ -352,238 +909,163 @@ fn remove_first_and_last(value: &str) -> &str {
     chars.next_back();
     chars.as_str()
 }
-impl AstNodeValue {
-    pub fn from_string(
-        data: &str,
-        variable_list: &std::collections::BTreeMap<String, AstNodeValue>,
-    ) -> Self {
-        let use_data = data.trim();
-        if use_data.starts_with("\"") && use_data.ends_with("\"") {
-            return Self::String(remove_first_and_last(use_data.trim()).to_string());
-        } else if use_data.starts_with("(")
-            && use_data.ends_with(")")
-            && !check_string_is_math_operation(use_data)
-        {
-            return Self::from_string(remove_first_and_last(use_data.trim()).trim(), variable_list);
-        } else if use_data.parse::<f64>().is_ok() {
-            return Self::Number(use_data.parse::<f64>().unwrap());
-        } else if use_data == "true" {
-            return Self::Bool(true);
-        } else if use_data == "false" {
-            return Self::Bool(false);
-        } else if use_data.len() > 0 && variable_list.contains_key(&use_data.to_string()) {
-            return Self::Variable(use_data.to_string());
-        } else if check_string_is_math_operation(use_data) {
-            let node = parse_math_operation(use_data, variable_list);
-            return Self::Operation(Box::new(node));
+/// numbers are read by `expr::parse_expression` so `-1`, `0xFF` and `1e-3` are the same
+/// literal everywhere. `data` start at `offset` in `ast`. `None` when the value can not
+/// be read, the reason is in `context.errors`
+fn parse_value(
+    data: &str,
+    offset: usize,
+    context: &mut ContextType,
+    ast: &mut Ast,
+) -> Option<ExprId> {
+    let use_data = data.trim();
+    let offset = offset + data.len() - data.trim_start().len();
+    if let Some(expr) = expr::parse_expression(use_data, offset, context, ast) {
+        Some(expr)
+    } else {
+        context.errors.push(if use_data.is_empty() {
+            "thiếu một giá trị".to_string()
         } else {
-            return Self::default();
-        }
+            format!("không hiểu giá trị `{}`", use_data)
+        });
+        None
     }
 }
-fn parse_math_operation(
-    data: &str,
-    variable_list: &std::collections::BTreeMap<String, AstNodeValue>,
-) -> AstNode {
-    let mut result = AstNode::default();
-    result.raw = data.to_string();
-    if {
-        if !data.starts_with("-") {
-            false
-        } else {
-            let new_string = {
-                let mut d = data.chars();
-                d.next();
-                d.as_str()
-            };
-            new_string.parse::<f64>().is_ok()
-        }
-    } {
-        result.op = Operation::Value(ValueMetaType::MathValue(AstNodeValue::from_string(
-            data,
-            variable_list,
-        )));
-        return result;
-    }
-    if !check_string_is_math_operation(data) {
-        if data.starts_with("\"") && data.ends_with("\"") {
-            panic!("cant calculator with string");
-        } else if (data == "true") | (data == "false") {
-            panic!("cant calculator with bool");
-        } else {
-            result.op = Operation::Value(ValueMetaType::MathValue(AstNodeValue::from_string(
-                data,
-                variable_list,
-            )))
-        }
-    } else {
-        let mut bra = 0;
-        let mut tmp = "".to_string();
-        let char_list_vec = data
-            .to_string()
-            .replace(" ", "")
-            .chars()
+
+/// a keyword pack of `config/packs`, read like the benchmark does
+#[cfg(test)]
+pub(crate) fn test_pack(name: &str) -> KeywordConfig {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join(format!("../config/packs/{}.toml", name));
+    load_config(&path).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the line, column and text of a statement with its doc
+    type Statement = (usize, usize, String, Option<String>);
+
+    fn split(data: &str) -> (Vec<Statement>, Vec<String>) {
+        let mut errors = vec![];
+        let statements = split_statements(data, &test_pack("vi"), &mut errors)
+            .into_iter()
+            .map(|s| (s.span.line, s.span.start, s.text, s.doc.map(|(_, doc)| doc)))
+            .collect();
+        (statements, errors.into_iter().map(|e| e.message).collect())
+    }
+
+    fn texts(data: &str) -> Vec<String> {
+        let (statements, errors) = split(data);
+        assert_eq!(errors, Vec::<String>::new());
+        statements.into_iter().map(|(_, _, text, _)| text).collect()
+    }
+
+    #[test]
+    fn semicolons_separate_statements() {
+        assert_eq!(
+            split("var a = 1; var b = 2\n   in: a;\n").0,
+            [
+                (1, 0, "var a = 1".to_string(), None),
+                (1, 11, "var b = 2".to_string(), None),
+                (2, 3, "in: a".to_string(), None)
+            ]
+        );
+        // inside a string or a parenthesis `;` is not a separator
+        assert_eq!(texts("in: \"a; b\"\n"), ["in: \"a; b\""]);
+        assert_eq!(texts("in: (1;2)\n"), ["in: (1;2)"]);
+        // the arguments of `in:` stop at the `;`
+        let ast = parse_string_to_ast("in: 1, 2; in: 3\n".to_string(), &test_pack("vi")).unwrap();
+        let args = ast
+            .body
+            .iter()
+            .map(|id| match &ast[*id] {
+                Stmt::Call { call, .. } => call.args.iter().map(|a| ast.raw(*a)).collect(),
+                _ => vec![],
+            })
             .collect::<Vec<_>>();
-        let mut char_list_iter = char_list_vec.clone().into_iter();
-        let mut op: Vec<_> = vec![];
-        let mut value = vec![];
-        while let Some(mut v) = char_list_iter.next() {
-            let mut push_op = IntOperationType::Plus;
-            tmp.push(v);
-            if v == '(' {
-                bra = bra + 1
-            }
-            if v == ')' {
-                bra = bra - 1;
-            }
+        assert_eq!(args, [vec!["1", "2"], vec!["3"]]);
+    }
 
-            if bra == 0 {
-                let mut pushable = true;
-                for o in OPS_LIST.clone() {
-                    if v.to_string() == o {
-                        push_op = IntOperationType::from_string_symbol(&v.to_string());
-                        tmp = "".to_string();
-                        pushable = false
-                    }
-                }
-                if v.is_numeric() {
-                    while let Some(ch) = char_list_iter.next() {
-                        #[allow(unused_assignments)]
-                        if ch.is_numeric() || ch == '.' {
-                            tmp.push(ch);
-                        } else {
-                            v = ch;
-                            break;
-                        }
-                    }
-                }
-                for o in OPS_LIST.clone() {
-                    if v.to_string() == o {
-                        push_op = IntOperationType::from_string_symbol(&v.to_string());
-                    }
-                }
-                op.push(push_op);
-                if tmp.len() > 0 && pushable {
-                    value.push(
-                        if tmp.clone().starts_with("(") && tmp.clone().ends_with(")") {
-                            remove_first_and_last(&tmp.clone()).to_string()
-                        } else {
-                            tmp.clone()
-                        },
-                    );
-                    tmp = "".to_string();
-                }
-            }
-        }
+    #[test]
+    fn statements_continue_on_the_next_line() {
+        // after an operator, a comma or a word operator
+        assert_eq!(
+            texts("var a = 1 +\n    2\nin: a,\n  b\nvar c = a cộng\n b\n"),
+            ["var a = 1 + 2", "in: a, b", "var c = a cộng b"]
+        );
+        // while a parenthesis or a bracket is open
+        assert_eq!(
+            texts("var a = (1\n+ 2)\nin: s[1\n..2]\n"),
+            ["var a = (1 + 2)", "in: s[1 ..2]"]
+        );
+        // a line starting with an operator is a statement of its own
+        assert_eq!(texts("var a = 1\n+ 2\n"), ["var a = 1", "+ 2"]);
+        // a `"""` string keep its lines
+        assert_eq!(
+            split("var s = \"\"\"a\nb\"\"\"\nin: s\n").0,
+            [
+                (1, 0, "var s = \"\"\"a\nb\"\"\"".to_string(), None),
+                (3, 0, "in: s".to_string(), None)
+            ]
+        );
+    }
 
-        // println!("data : {:?}, value : {:#?}, op :{:#?}", data, value, op);
-        {
-            let mut left_op = "".to_string();
-            let mut right_op = "".to_string();
-            let mut operation = IntOperationType::None;
-            for i in 0..value.len() {
-                if i < (((value.len() as u32 as f64) / 2.0).round() as i32)
-                    .try_into()
-                    .unwrap()
-                {
-                    left_op.push_str(&op[i].to_symbol_string());
-                    left_op.push_str(
-                        &(if op[i].to_symbol_string() == "-" {
-                            format!("({})", value[i].clone())
-                        } else {
-                            value[i].clone()
-                        }),
-                    );
-                } else {
-                    if let IntOperationType::None = operation {
-                        operation = op[i].clone();
-                    }
-                    right_op.push_str(
-                        &op[if i + 1 >= op.len() {
-                            op.len() - 1
-                        } else {
-                            i + 1
-                        }]
-                        .to_symbol_string(),
-                    );
-                    right_op.push_str(
-                        &(if op[if i + 1 == op.len() { op.len()-1 } else { i + 1 }].to_symbol_string()
-                            == "-"
-                        {
-                            format!("({})", value[i].clone())
-                        } else {
-                            value[i].clone()
-                        }),
-                    );
-                }
-            }
+    #[test]
+    fn parenthesis_not_closed() {
+        assert_eq!(
+            split("in: (1 + 2\n").1,
+            ["dấu ngoặc mở ở đây chưa được đóng đến hết tệp"]
+        );
+    }
 
-            result.op = Operation::IntOperation(operation);
-
-            if left_op.starts_with("-") {
-                let mut left_node = AstNode::default();
-                left_node.raw = left_op.to_string();
-                left_node.op = Operation::IntOperation(IntOperationType::Minus);
-                left_node
-                    .left
-                    .push(parse_math_operation("0", variable_list));
-                left_node.right.push(parse_math_operation(
-                    &{
-                        let mut new_op = left_op.to_string();
-                        new_op.remove(0);
-                        remove_first_and_last(&new_op).to_string()
-                    },
-                    variable_list,
-                ));
-                result.left.push(left_node);
-            } else {
-                let mut new_op = left_op.to_string();
-                new_op.remove(0);
-                result
-                    .left
-                    .push(parse_math_operation(&new_op, variable_list));
-            }
-            if right_op.starts_with("-") {
-                let mut right_node = AstNode::default();
-                right_node.raw = right_op.to_string();
-                right_node.op = Operation::IntOperation(IntOperationType::Minus);
-                right_node
-                    .left
-                    .push(parse_math_operation("0", variable_list));
-                right_node.right.push(parse_math_operation(
-                    &{
-                        let mut new_op = right_op.to_string();
-                        new_op.remove(0);
-                        remove_first_and_last(&new_op).to_string()
-                    },
-                    variable_list,
-                ));
-                result.right.push(right_node);
-            } else {
-                let mut new_op = right_op.to_string();
-                if new_op.starts_with("+") {
-                    new_op.remove(0);
-                }
-                result
-                    .right
-                    .push(parse_math_operation(&new_op, variable_list));
-            }
-        }
+    #[test]
+    fn comments_are_removed() {
+        assert_eq!(texts("var a = 1 # giá trị\n# cả dòng\n"), ["var a = 1"]);
+        // a `#` in a string is text
+        assert_eq!(texts("in: \"#1\" # số\n"), ["in: \"#1\""]);
+        assert_eq!(texts("var a = #[ chú thích ]# 1\n"), ["var a =   1"]);
+        // a block comment can span lines and be nested
+        assert_eq!(texts("in: 1, #[ a\n b ]# 2\n"), ["in: 1,   2"]);
+        assert_eq!(
+            split("#[ a #[ b ]# c ]# in: 1\n").0,
+            [(1, 18, "in: 1".to_string(), None)]
+        );
+        assert_eq!(
+            split("in: 1\n#[ a #[ b ]#\nin: 2\n").1,
+            ["chú thích `#[` chưa được đóng bằng `]#`"]
+        );
     }
-    result
-}
 
-fn check_string_is_math_operation(data: &str) -> bool {
-    let mut result = false;
-    let mut setted = false;
-    if data.starts_with("\"") && data.ends_with("\"") {
-        return false;
+    #[test]
+    fn doc_comments_go_to_the_next_declaration() {
+        assert_eq!(
+            split("## giá\n  ##có thuế\nvar a = 1\nvar b = 2\n").0,
+            [
+                (
+                    3,
+                    0,
+                    "var a = 1".to_string(),
+                    Some("giá\ncó thuế".to_string())
+                ),
+                (4, 0, "var b = 2".to_string(), None)
+            ]
+        );
+        let ast =
+            parse_string_to_ast("## giá\nhằng a = 1\n".to_string(), &test_pack("vi")).unwrap();
+        assert!(matches!(&ast[ast.body[0]], Stmt::Let { doc: Some(doc), .. } if doc == "giá"));
     }
-    for o in OPS_LIST.clone() {
-        if data.contains(&o) && !setted {
-            setted = true;
-            result = data.contains(&o);
-        }
+
+    #[test]
+    fn doc_comments_without_declaration() {
+        let message = "chú thích `##` phải đứng ngay trước một khai báo `var` hoặc `hằng`";
+        assert_eq!(split("var a = 1\n## lạc\n").1, [message]);
+        let errors = parse_string_to_ast("## lạc\nin: 1\n".to_string(), &test_pack("vi"))
+            .unwrap_err()
+            .into_iter()
+            .map(|e| (e.span.line, e.message))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, [(1, message.to_string())]);
     }
-    return result;
 }
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{literal, KeywordConfig};
+
+lazy_static! {
+    /// `#! pack: en` on the first line choose the keyword pack of the program
+    static ref PACK_PRAGMA_REGEX: Regex = Regex::new(r"^#!\s*pack\s*:\s*(\w+)\s*$").unwrap();
+}
+
+/// the pack named by the pragma of `source`, `None` for the default one
+pub fn source_pack(source: &str) -> Option<String> {
+    PACK_PRAGMA_REGEX
+        .captures(source.lines().next().unwrap_or_default())
+        .map(|p| p[1].to_string())
+}
+
+enum Piece {
+    Word(String),
+    Text(String),
+}
+
+/// cut the code into words and the text between them, string literals and comments are
+/// text
+fn split_words(source: &str) -> Vec<Piece> {
+    let mut result = vec![];
+    let mut text = String::new();
+    let mut word = String::new();
+    let mut in_line_comment = false;
+    // the `#[` of the block comments still open, they can be nested
+    let mut comments = 0usize;
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let in_code = !in_line_comment && comments == 0;
+        if let Some(end) = literal::string_literal_end(&chars, i).filter(|_| in_code) {
+            if !word.is_empty() {
+                result.push(Piece::Word(std::mem::take(&mut word)));
+            }
+            text.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        if in_code && (c.is_alphanumeric() || c == '_') {
+            if !text.is_empty() {
+                result.push(Piece::Text(std::mem::take(&mut text)));
+            }
+            word.push(c);
+            i += 1;
+            continue;
+        }
+        if !word.is_empty() {
+            result.push(Piece::Word(std::mem::take(&mut word)));
+        }
+        let mut width = 1;
+        if c == '\n' {
+            in_line_comment = false;
+        } else if in_line_comment {
+        } else if c == '#' && next == Some('[') {
+            comments += 1;
+            width = 2;
+        } else if comments > 0 {
+            if c == ']' && next == Some('#') {
+                comments -= 1;
+                width = 2;
+            }
+        } else {
+            in_line_comment = c == '#';
+        }
+        text.extend(&chars[i..i + width]);
+        i += width;
+    }
+    if !word.is_empty() {
+        result.push(Piece::Word(word));
+    }
+    if !text.is_empty() {
+        result.push(Piece::Text(text));
+    }
+    result
+}
+
+/// how many pieces from `start` the words of `phrase` take, they must be separated by
+/// spaces only: `chia cho`
+fn match_phrase(pieces: &[Piece], start: usize, phrase: &[String]) -> Option<usize> {
+    let mut i = start;
+    for (j, word) in phrase.iter().enumerate() {
+        if j > 0 {
+            match pieces.get(i) {
+                Some(Piece::Text(t)) if t.chars().all(|c| c == ' ' || c == '\t') => i += 1,
+                _ => return None,
+            }
+        }
+        match pieces.get(i) {
+            Some(Piece::Word(w)) if w == word => i += 1,
+            _ => return None,
+        }
+    }
+    Some(i - start)
+}
+
+/// call `rewrite` on every word of the code, or on every phrase of `phrases` written with
+/// its words one space apart. String literals and comments are copied as they are
+pub(crate) fn rewrite_words(
+    source: &str,
+    phrases: &[Vec<String>],
+    mut rewrite: impl FnMut(&str) -> String,
+) -> String {
+    let mut result = String::new();
+    let pieces = split_words(source);
+    let mut i = 0;
+    'pieces: while i < pieces.len() {
+        match &pieces[i] {
+            Piece::Text(text) => result.push_str(text),
+            Piece::Word(word) => {
+                for phrase in phrases {
+                    if let Some(length) = match_phrase(&pieces, i, phrase) {
+                        result.push_str(&rewrite(&phrase.join(" ")));
+                        i += length;
+                        continue 'pieces;
+                    }
+                }
+                result.push_str(&rewrite(word));
+            }
+        }
+        i += 1;
+    }
+    result
+}
+
+/// every keyword of a config with what it mean: `in` → `print`, `cộng` → `+`
+fn keyword_meanings(config: &KeywordConfig) -> BTreeMap<&str, &str> {
+    config
+        .default_function
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.r#type.as_str()))
+        .chain(
+            config
+                .operators
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        )
+        .collect()
+}
+
+/// rewrite a program from the keywords of `from` to the ones of `to` and set its pragma
+/// to `pack`. When `to` has many keywords for the same meaning (`in`, `out` and
+/// `in_ra_màn_hình` all print) the first one in alphabetical order is used
+pub fn translate(
+    source: &str,
+    from: &KeywordConfig,
+    to: &KeywordConfig,
+    pack: &str,
+) -> Result<String, Vec<String>> {
+    let from_meanings = keyword_meanings(from);
+    let to_meanings = keyword_meanings(to);
+    let mut to_keywords = BTreeMap::<&str, &str>::new();
+    for (keyword, meaning) in &to_meanings {
+        to_keywords.entry(meaning).or_insert(keyword);
+    }
+    // the same word used on many lines is reported once
+    let mut errors = BTreeSet::new();
+    let body = match source_pack(source) {
+        Some(_) => source.split_once('\n').map_or("", |(_, body)| body),
+        None => source,
+    };
+    let mut phrases = from
+        .operators
+        .keys()
+        .map(|k| k.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .filter(|words| words.len() > 1)
+        .collect::<Vec<_>>();
+    phrases.sort_by_key(|words| std::cmp::Reverse(words.len()));
+    let body = rewrite_words(body, &phrases, |word| match from_meanings.get(word) {
+        Some(meaning) => match to_keywords.get(meaning) {
+            Some(keyword) => keyword.to_string(),
+            // a word operator become its symbol when the other pack has no word for it
+            None if from.operators.contains_key(word) => meaning.to_string(),
+            None => {
+                errors.insert(format!(
+                    "gói `{}` không có từ khoá nào nghĩa là `{}` để thay cho `{}`",
+                    pack, meaning, word
+                ));
+                word.to_string()
+            }
+        },
+        None => {
+            // a name of the program that is a keyword in the other pack would change meaning
+            if to_meanings.contains_key(word) {
+                errors.insert(format!(
+                    "tên `{}` là từ khoá trong gói `{}`, hãy đổi tên trước khi dịch",
+                    word, pack
+                ));
+            }
+            word.to_string()
+        }
+    });
+    if !errors.is_empty() {
+        return Err(errors.into_iter().collect());
+    }
+    Ok(format!("#! pack: {}\n{}", pack, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::test_pack;
+
+    #[test]
+    fn translate_keywords_and_operators() {
+        let (vi, en) = (test_pack("vi"), test_pack("en"));
+        let source = "var tuổi = 20 cộng 1\nin: \"var\", tuổi # in ra\n";
+        assert_eq!(
+            translate(source, &vi, &en, "en").unwrap(),
+            "#! pack: en\nvar tuổi = 20 plus 1\nprint: \"var\", tuổi # in ra\n"
+        );
+    }
+
+    #[test]
+    fn report_a_word_once() {
+        let (vi, en) = (test_pack("vi"), test_pack("en"));
+        let source = "var print = 1\nvar end = 2\nin: print, end\n";
+        assert_eq!(
+            translate(source, &vi, &en, "en").unwrap_err(),
+            [
+                "tên `end` là từ khoá trong gói `en`, hãy đổi tên trước khi dịch",
+                "tên `print` là từ khoá trong gói `en`, hãy đổi tên trước khi dịch"
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use super::{Ast, ExprId, ExprKind, Stmt, StmtId};
+
+/// walk a program without writing the recursion again. Every method visit the children
+/// by default, a pass override the ones it care about and call the `walk_*` function
+/// inside to keep going down
+///
+/// ```
+/// # use vipl::parse::{walk_ast, walk_expr, Ast, ExprId, ExprKind, Visitor};
+/// struct CountVariables(usize);
+///
+/// impl<'ast> Visitor<'ast> for CountVariables {
+///     fn visit_expr(&mut self, ast: &'ast Ast, id: ExprId) {
+///         if let ExprKind::Variable(_) = ast[id].kind {
+///             self.0 += 1;
+///         }
+///         walk_expr(self, ast, id);
+///     }
+/// }
+///
+/// let mut count = CountVariables(0);
+/// walk_ast(&mut count, &Ast::default());
+/// assert_eq!(count.0, 0);
+/// ```
+pub trait Visitor<'ast> {
+    fn visit_stmt(&mut self, ast: &'ast Ast, id: StmtId) {
+        walk_stmt(self, ast, id);
+    }
+    fn visit_expr(&mut self, ast: &'ast Ast, id: ExprId) {
+        walk_expr(self, ast, id);
+    }
+}
+
+/// every statement outside of any block, in order
+pub fn walk_ast<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ast: &'ast Ast) {
+    for &id in &ast.body {
+        visitor.visit_stmt(ast, id);
+    }
+}
+
+/// the value of a declaration or an assignment, the arguments of a call or the
+/// statements of a block
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ast: &'ast Ast, id: StmtId) {
+    match &ast[id] {
+        Stmt::Let { value: None, .. } => {}
+        &Stmt::Let {
+            value: Some(value), ..
+        }
+        | &Stmt::Assign { value, .. } => visitor.visit_expr(ast, value),
+        Stmt::Call { call, .. } => {
+            for &args in &call.args {
+                visitor.visit_expr(ast, args);
+            }
+        }
+        Stmt::Block { body, .. } => {
+            for &id in body {
+                visitor.visit_stmt(ast, id);
+            }
+        }
+    }
+}
+
+/// the operands, arguments and positions of an expression, left to right
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, ast: &'ast Ast, id: ExprId) {
+    match &ast[id].kind {
+        ExprKind::String(_)
+        | ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Decimal(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Variable(_) => {}
+        &ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(ast, left);
+            visitor.visit_expr(ast, right);
+        }
+        ExprKind::Call(call) => {
+            for &args in &call.args {
+                visitor.visit_expr(ast, args);
+            }
+        }
+        &ExprKind::Index { target, index } => {
+            visitor.visit_expr(ast, target);
+            visitor.visit_expr(ast, index);
+        }
+        &ExprKind::Slice { target, start, end } => {
+            visitor.visit_expr(ast, target);
+            visitor.visit_expr(ast, start);
+            if let Some(end) = end {
+                visitor.visit_expr(ast, end);
+            }
+        }
+    }
+}
+
+/// like `Visitor` but the pass can change the nodes it visit, a formatter or the type
+/// checker writing `value_type`
+pub trait VisitorMut {
+    fn visit_stmt_mut(&mut self, ast: &mut Ast, id: StmtId) {
+        walk_stmt_mut(self, ast, id);
+    }
+    fn visit_expr_mut(&mut self, ast: &mut Ast, id: ExprId) {
+        walk_expr_mut(self, ast, id);
+    }
+}
+
+pub fn walk_ast_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ast: &mut Ast) {
+    for id in ast.body.clone() {
+        visitor.visit_stmt_mut(ast, id);
+    }
+}
+
+/// the children are read before they are visited, a pass can replace them on the way
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ast: &mut Ast, id: StmtId) {
+    match &ast[id] {
+        Stmt::Let { value: None, .. } => {}
+        &Stmt::Let {
+            value: Some(value), ..
+        }
+        | &Stmt::Assign { value, .. } => visitor.visit_expr_mut(ast, value),
+        Stmt::Call { call, .. } => {
+            for args in call.args.clone() {
+                visitor.visit_expr_mut(ast, args);
+            }
+        }
+        Stmt::Block { body, .. } => {
+            for id in body.clone() {
+                visitor.visit_stmt_mut(ast, id);
+            }
+        }
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ast: &mut Ast, id: ExprId) {
+    match &ast[id].kind {
+        ExprKind::String(_)
+        | ExprKind::Int(_)
+        | ExprKind::Float(_)
+        | ExprKind::Decimal(_)
+        | ExprKind::Bool(_)
+        | ExprKind::Variable(_) => {}
+        &ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr_mut(ast, left);
+            visitor.visit_expr_mut(ast, right);
+        }
+        ExprKind::Call(call) => {
+            for args in call.args.clone() {
+                visitor.visit_expr_mut(ast, args);
+            }
+        }
+        &ExprKind::Index { target, index } => {
+            visitor.visit_expr_mut(ast, target);
+            visitor.visit_expr_mut(ast, index);
+        }
+        &ExprKind::Slice { target, start, end } => {
+            visitor.visit_expr_mut(ast, target);
+            visitor.visit_expr_mut(ast, start);
+            if let Some(end) = end {
+                visitor.visit_expr_mut(ast, end);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{BinaryOp, Call, Expr, Span};
+
+    /// `var x = a + b` then `khối in: x hết`
+    fn small_ast() -> Ast {
+        let mut ast = Ast::default();
+        let raw = ast.push_text("a + b");
+        let a = ast.push_expr(Expr::new(ExprKind::Variable("a".to_string()), raw));
+        let b = ast.push_expr(Expr::new(ExprKind::Variable("b".to_string()), raw));
+        let sum = ast.push_expr(Expr::new(
+            ExprKind::Binary {
+                op: BinaryOp::Plus,
+                left: a,
+                right: b,
+            },
+            raw,
+        ));
+        let declaration = ast.push_stmt(Stmt::Let {
+            name: "x".to_string(),
+            constant: false,
+            annotation: None,
+            value: Some(sum),
+            doc: None,
+            value_type: None,
+            span: Span::default(),
+        });
+        let raw = ast.push_text("x");
+        let x = ast.push_expr(Expr::new(ExprKind::Variable("x".to_string()), raw));
+        let print = ast.push_stmt(Stmt::Call {
+            call: Call {
+                function: "in".to_string(),
+                args: vec![x],
+            },
+            span: Span::default(),
+        });
+        let block = ast.push_stmt(Stmt::Block {
+            body: vec![print],
+            span: Span::default(),
+        });
+        ast.body = vec![declaration, block];
+        ast
+    }
+
+    struct VariableNames(Vec<String>);
+
+    impl<'ast> Visitor<'ast> for VariableNames {
+        fn visit_expr(&mut self, ast: &'ast Ast, id: ExprId) {
+            if let ExprKind::Variable(name) = &ast[id].kind {
+                self.0.push(name.clone());
+            }
+            walk_expr(self, ast, id);
+        }
+    }
+
+    struct Uppercase;
+
+    impl VisitorMut for Uppercase {
+        fn visit_expr_mut(&mut self, ast: &mut Ast, id: ExprId) {
+            if let ExprKind::Variable(name) = &mut ast[id].kind {
+                *name = name.to_uppercase();
+            }
+            walk_expr_mut(self, ast, id);
+        }
+    }
+
+    fn variable_names(ast: &Ast) -> Vec<String> {
+        let mut names = VariableNames(vec![]);
+        walk_ast(&mut names, ast);
+        names.0
+    }
+
+    #[test]
+    fn visit_every_expression_in_order() {
+        assert_eq!(variable_names(&small_ast()), ["a", "b", "x"]);
+    }
+
+    #[test]
+    fn visit_mut_change_the_nodes() {
+        let mut ast = small_ast();
+        walk_ast_mut(&mut Uppercase, &mut ast);
+        assert_eq!(variable_names(&ast), ["A", "B", "X"]);
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use crate::parse::{
+    strip_accents, walk_ast, walk_expr, walk_stmt, Ast, BuiltinFunction, ExprId, ExprKind,
+    KeywordConfig, RoundingMode, Span, Stmt, StmtId, ValueType, Visitor,
+};
+
+#[derive(Debug, Clone)]
+pub struct NameError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl NameError {
+    pub fn new(span: Span, message: String) -> Self {
+        Self { span, message }
+    }
+    pub fn report(&self, source: &str) -> String {
+        format!(
+            "lỗi tên ở dòng {}:{}: {}\n{}",
+            self.span.line,
+            self.span.start + 1,
+            self.message,
+            self.span.show(source)
+        )
+    }
+}
+
+/// check that every name of the program is declared before it is used and every function
+/// exist. The parser only read the names, it is here that they are looked up
+pub fn resolve_ast(ast: &Ast, config: &KeywordConfig) -> Result<(), Vec<NameError>> {
+    let mut resolver = Resolver {
+        config,
+        scope: Default::default(),
+        span: Default::default(),
+        errors: vec![],
+    };
+    walk_ast(&mut resolver, ast);
+    if resolver.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(resolver.errors)
+    }
+}
+
+struct Resolver<'a> {
+    config: &'a KeywordConfig,
+    /// the variables and constants declared so far, `true` for a constant. A block drop
+    /// the ones it declared
+    scope: BTreeMap<String, bool>,
+    /// the statement being resolved, the errors point at it
+    span: Span,
+    errors: Vec<NameError>,
+}
+
+impl<'a> Visitor<'a> for Resolver<'a> {
+    fn visit_stmt(&mut self, ast: &'a Ast, id: StmtId) {
+        self.span = ast[id].span();
+        match &ast[id] {
+            Stmt::Let { name, constant, .. } => {
+                // `var x = x + 1` use the `x` declared before, not the new one
+                walk_stmt(self, ast, id);
+                self.scope.insert(name.clone(), *constant);
+            }
+            Stmt::Assign { name, .. } => {
+                self.check_variable(name);
+                if self.scope.get(name) == Some(&true) {
+                    self.errors.push(NameError::new(
+                        self.span,
+                        format!("`{}` là hằng, không thể gán lại giá trị", name),
+                    ));
+                }
+                walk_stmt(self, ast, id);
+            }
+            Stmt::Call { call, .. } => {
+                self.check_statement(&call.function);
+                walk_stmt(self, ast, id);
+            }
+            Stmt::Block { .. } => {
+                let scope = self.scope.clone();
+                walk_stmt(self, ast, id);
+                self.scope = scope;
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, ast: &'a Ast, id: ExprId) {
+        match &ast[id].kind {
+            ExprKind::Variable(name) => self.check_variable(name),
+            ExprKind::Call(call) => self.check_function(&call.function),
+            _ => {}
+        }
+        walk_expr(self, ast, id);
+    }
+}
+
+impl<'a> Resolver<'a> {
+    fn check_variable(&mut self, name: &str) {
+        if self.scope.contains_key(name) {
+            return;
+        }
+        let message = format!("biến `{}` chưa được khai báo", name);
+        let suggestion = suggest(name, self.scope.keys().map(String::as_str));
+        self.errors.push(NameError::new(
+            self.span,
+            with_suggestion(message, suggestion),
+        ));
+    }
+
+    /// only a print keyword can be a statement: `độ_dài: "a"` would compute a value and
+    /// drop it, `var: 1` is not a call at all
+    fn check_statement(&mut self, name: &str) {
+        let message = match self.config.default_function.get(name) {
+            Some(f) if f.r#type == "print" => return,
+            Some(f)
+                if BuiltinFunction::from_config_type(&f.r#type).is_some()
+                    || RoundingMode::from_config_type(&f.r#type).is_some()
+                    || ValueType::from_config_type(&f.r#type).is_some() =>
+            {
+                format!(
+                    "`{}` cho ra một giá trị, hãy dùng nó trong một biểu thức: `in: {}(...)`",
+                    name, name
+                )
+            }
+            Some(_) => format!("`{}` là từ khoá, không phải là một lệnh", name),
+            None => {
+                let print_keywords = self
+                    .config
+                    .default_function
+                    .iter()
+                    .filter(|(_, f)| f.r#type == "print")
+                    .map(|(k, _)| k.as_str());
+                with_suggestion(
+                    format!("không có lệnh nào tên là `{}`", name),
+                    suggest(name, print_keywords),
+                )
+            }
+        };
+        self.errors.push(NameError::new(self.span, message));
+    }
+
+    /// a call inside an expression is a function of the pack or the conversion to a type:
+    /// `số_nguyên(x)`
+    fn check_function(&mut self, name: &str) {
+        let types = self.config.type_keyword.keys();
+        if !self.config.default_function.contains_key(name) && !types.clone().any(|t| t == name) {
+            let message = format!("không có hàm nào tên là `{}`", name);
+            let candidates = self.config.default_function.keys().chain(types);
+            let suggestion = suggest(name, candidates.map(String::as_str));
+            self.errors.push(NameError::new(
+                self.span,
+                with_suggestion(message, suggestion),
+            ));
+        }
+    }
+}
+
+fn with_suggestion(message: String, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(s) => format!("{}, có phải bạn muốn viết `{}`?", message, s),
+        None => message,
+    }
+}
+
+/// the known name closest to `name`, the accents and the case are not counted so `tuoi`
+/// find `tuổi`. A name too far from every known one has no suggestion
+fn suggest<'a>(name: &str, known: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let plain = |s: &str| strip_accents(s).to_lowercase().chars().collect::<Vec<_>>();
+    let target = plain(name);
+    known
+        .map(|k| (edit_distance(&target, &plain(k)), k))
+        .filter(|(distance, _)| *distance * 3 <= target.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k)
+}
+
+/// the Levenshtein distance: how many characters must be inserted, removed or replaced
+/// to go from `a` to `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(ca != cb);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_string_to_ast, test_pack};
+
+    fn errors(source: &str) -> Vec<String> {
+        let config = test_pack("vi");
+        let ast = parse_string_to_ast(source.to_string(), &config).unwrap();
+        match resolve_ast(&ast, &config) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.message).collect(),
+        }
+    }
+
+    #[test]
+    fn only_print_is_a_statement() {
+        assert_eq!(
+            errors("in: độ_dài(\"ab\")\nin_ra_màn_hình: 1\n"),
+            [] as [&str; 0]
+        );
+        assert_eq!(
+            errors("độ_dài: \"a\"\nsố_nguyên: 1\nvar: 1\n"),
+            [
+                "`độ_dài` cho ra một giá trị, hãy dùng nó trong một biểu thức: `in: độ_dài(...)`",
+                "`số_nguyên` cho ra một giá trị, hãy dùng nó trong một biểu thức: `in: số_nguyên(...)`",
+                "`var` là từ khoá, không phải là một lệnh",
+            ]
+        );
+    }
+
+    #[test]
+    fn undeclared_names() {
+        assert_eq!(
+            errors("var tuổi = 1\nin: tuoi\nkhối\nvar x = 1\nhết\nx = 2\n"),
+            [
+                "biến `tuoi` chưa được khai báo, có phải bạn muốn viết `tuổi`?",
+                "biến `x` chưa được khai báo",
+            ]
+        );
+        assert_eq!(
+            errors("hằng k = 1\nk = 2\n"),
+            ["`k` là hằng, không thể gán lại giá trị"]
+        );
+    }
+
+    fn distance(a: &str, b: &str) -> usize {
+        edit_distance(
+            &a.chars().collect::<Vec<_>>(),
+            &b.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn levenshtein_distance() {
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "tuổi"), 4);
+        assert_eq!(distance("tuổi", ""), 4);
+        assert_eq!(distance("tuổi", "tuổi"), 0);
+        assert_eq!(distance("tuoi", "tuổi"), 1);
+    }
+
+    #[test]
+    fn suggest_ignore_accents_and_case() {
+        let known = ["tên", "tuổi", "điểm_số"];
+        assert_eq!(suggest("tuoi", known.into_iter()), Some("tuổi"));
+        assert_eq!(suggest("TUOI", known.into_iter()), Some("tuổi"));
+        assert_eq!(suggest("diem_so", known.into_iter()), Some("điểm_số"));
+    }
+
+    #[test]
+    fn suggest_closest_name() {
+        let known = ["tên", "tuổi"];
+        assert_eq!(suggest("ten", known.into_iter()), Some("tên"));
+        assert_eq!(suggest("tuoii", known.into_iter()), Some("tuổi"));
+    }
+
+    #[test]
+    fn no_suggestion_for_a_far_name() {
+        let known = ["tên", "tuổi"];
+        assert_eq!(suggest("số_lượng", known.into_iter()), None);
+        assert_eq!(suggest("x", known.into_iter()), None);
+        assert_eq!(suggest("tuoi", std::iter::empty()), None);
+    }
+}
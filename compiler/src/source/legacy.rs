@@ -0,0 +1,279 @@
+use unicode_normalization::UnicodeNormalization;
+
+const GRAVE: char = '\u{300}';
+const ACUTE: char = '\u{301}';
+const TILDE: char = '\u{303}';
+const HOOK: char = '\u{309}';
+const DOT: char = '\u{323}';
+const CIRCUMFLEX: char = '\u{302}';
+const BREVE: char = '\u{306}';
+
+/// the bytes 0x80..=0x9F of Windows-1252, the fonts of VNI and the code page 1258 are
+/// drawn on top of it
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => CP1252_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// Windows-1258 is Windows-1252 with the Vietnamese letters and the tones written as
+/// combining marks after the vowel
+pub fn decode_windows_1258(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match b {
+            0x8A => '\u{8a}',
+            0x8E => '\u{8e}',
+            0x9A => '\u{9a}',
+            0x9E => '\u{9e}',
+            0xC3 => 'Ă',
+            0xCC => GRAVE,
+            0xD0 => 'Đ',
+            0xD2 => HOOK,
+            0xD5 => 'Ơ',
+            0xDD => 'Ư',
+            0xDE => TILDE,
+            0xE3 => 'ă',
+            0xEC => ACUTE,
+            0xF0 => 'đ',
+            0xF2 => DOT,
+            0xF5 => 'ơ',
+            0xFD => 'ư',
+            0xFE => '₫',
+            _ => windows_1252(*b),
+        })
+        .collect::<String>()
+        .nfc()
+        .collect()
+}
+
+/// TCVN3 (the `.VnTime` fonts), every letter is one byte. Capital letters with a tone are
+/// drawn by another font (`.VnTimeH`) with the same bytes, so they come back lowercase
+pub fn decode_tcvn3(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match b {
+            0xA1 => 'Ă',
+            0xA2 => 'Â',
+            0xA3 => 'Ê',
+            0xA4 => 'Ô',
+            0xA5 => 'Ơ',
+            0xA6 => 'Ư',
+            0xA7 => 'Đ',
+            0xA8 => 'ă',
+            0xA9 => 'â',
+            0xAA => 'ê',
+            0xAB => 'ô',
+            0xAC => 'ơ',
+            0xAD => 'ư',
+            0xAE => 'đ',
+            0xB5 => 'à',
+            0xB6 => 'ả',
+            0xB7 => 'ã',
+            0xB8 => 'á',
+            0xB9 => 'ạ',
+            0xBB => 'ằ',
+            0xBC => 'ẳ',
+            0xBD => 'ẵ',
+            0xBE => 'ắ',
+            0xC6 => 'ặ',
+            0xC7 => 'ầ',
+            0xC8 => 'ẩ',
+            0xC9 => 'ẫ',
+            0xCA => 'ấ',
+            0xCB => 'ậ',
+            0xCC => 'è',
+            0xCE => 'ẻ',
+            0xCF => 'ẽ',
+            0xD0 => 'é',
+            0xD1 => 'ẹ',
+            0xD2 => 'ề',
+            0xD3 => 'ể',
+            0xD4 => 'ễ',
+            0xD5 => 'ế',
+            0xD6 => 'ệ',
+            0xD7 => 'ì',
+            0xD8 => 'ỉ',
+            0xDC => 'ĩ',
+            0xDD => 'í',
+            0xDE => 'ị',
+            0xDF => 'ò',
+            0xE1 => 'ỏ',
+            0xE2 => 'õ',
+            0xE3 => 'ó',
+            0xE4 => 'ọ',
+            0xE5 => 'ồ',
+            0xE6 => 'ổ',
+            0xE7 => 'ỗ',
+            0xE8 => 'ố',
+            0xE9 => 'ộ',
+            0xEA => 'ờ',
+            0xEB => 'ở',
+            0xEC => 'ỡ',
+            0xED => 'ớ',
+            0xEE => 'ợ',
+            0xEF => 'ù',
+            0xF1 => 'ủ',
+            0xF2 => 'ũ',
+            0xF3 => 'ú',
+            0xF4 => 'ụ',
+            0xF5 => 'ừ',
+            0xF6 => 'ử',
+            0xF7 => 'ữ',
+            0xF8 => 'ứ',
+            0xF9 => 'ự',
+            0xFA => 'ỳ',
+            0xFB => 'ỷ',
+            0xFC => 'ỹ',
+            0xFD => 'ý',
+            0xFE => 'ỵ',
+            _ => windows_1252(*b),
+        })
+        .collect()
+}
+
+/// the marks a VNI byte put on the vowel before it: `Vieät` is `Việt`. The byte of a
+/// capital letter is the one of the small letter minus 0x20, like in Latin-1
+fn vni_marks(byte: u8) -> Option<&'static [char]> {
+    Some(match byte | 0x20 {
+        0xE2 => &[CIRCUMFLEX],
+        0xEA => &[BREVE],
+        0xF8 => &[GRAVE],
+        0xF9 => &[ACUTE],
+        0xFB => &[HOOK],
+        0xF5 => &[TILDE],
+        0xEF => &[DOT],
+        0xE0 => &[CIRCUMFLEX, GRAVE],
+        0xE1 => &[CIRCUMFLEX, ACUTE],
+        0xE5 => &[CIRCUMFLEX, HOOK],
+        0xE3 => &[CIRCUMFLEX, TILDE],
+        0xE4 => &[CIRCUMFLEX, DOT],
+        0xE8 => &[BREVE, GRAVE],
+        0xE9 => &[BREVE, ACUTE],
+        0xFA => &[BREVE, HOOK],
+        0xFC => &[BREVE, TILDE],
+        0xEB => &[BREVE, DOT],
+        _ => return None,
+    })
+}
+
+/// the letters VNI write with one byte
+fn vni_letter(byte: u8) -> Option<char> {
+    Some(match byte {
+        0xF4 => 'ơ',
+        0xD4 => 'Ơ',
+        0xF6 => 'ư',
+        0xD6 => 'Ư',
+        0xF1 => 'đ',
+        0xD1 => 'Đ',
+        0xEC => 'ì',
+        0xCC => 'Ì',
+        0xED => 'í',
+        0xCD => 'Í',
+        0xE6 => 'ỉ',
+        0xC6 => 'Ỉ',
+        0xF3 => 'ĩ',
+        0xD3 => 'Ĩ',
+        0xF2 => 'ị',
+        0xD2 => 'Ị',
+        0xEE => 'ỵ',
+        0xCE => 'Ỵ',
+        _ => return None,
+    })
+}
+
+/// VNI Windows (the `VNI-Times` fonts), a vowel is followed by a byte drawing its marks
+pub fn decode_vni(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    for b in bytes {
+        let after_vowel = result
+            .chars()
+            .last()
+            .is_some_and(|c| "aeiouyơư".contains(c.to_lowercase().next().unwrap_or(c)));
+        if let Some(letter) = vni_letter(*b) {
+            result.push(letter);
+        } else if let Some(marks) = vni_marks(*b).filter(|_| after_vowel && *b >= 0xC0) {
+            result.extend(marks);
+        } else {
+            result.push(windows_1252(*b));
+        }
+    }
+    result.nfc().collect()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// the bytes of `text` in the encoding read by `decode`, found by decoding every
+    /// sequence of one or two bytes. One byte is taken first, unless it read differently
+    /// after a vowel: `à` is a VNI mark there
+    pub(in crate::source) fn encode(text: &str, decode: fn(&[u8]) -> String) -> Vec<u8> {
+        let mut table = HashMap::new();
+        let sequences = (0..=u8::MAX)
+            .map(|a| vec![a])
+            .chain((0..=u8::MAX).flat_map(|a| (0..=u8::MAX).map(move |b| vec![a, b])));
+        for bytes in sequences {
+            let text = decode(&bytes);
+            if decode(&[&b"a"[..], &bytes].concat()) == format!("a{}", text) {
+                table.entry(text).or_insert(bytes);
+            }
+        }
+        text.chars()
+            .flat_map(|c| match table.get(&c.to_string()) {
+                Some(bytes) => bytes.clone(),
+                None => panic!("`{}` không viết được trong bảng mã này", c),
+            })
+            .collect()
+    }
+
+    /// every vowel with every tone, `đ` and a few words. No capital letter has a tone,
+    /// TCVN3 read them back small
+    const SAMPLE: &str = "aàảãáạ ăằẳẵắặ âầẩẫấậ eèẻẽéẹ êềểễếệ iìỉĩíị oòỏõóọ ôồổỗốộ \
+                          ơờởỡớợ uùủũúụ ưừửữứự yỳỷỹýỵ đ Đ Ă Â Ê Ô Ơ Ư \
+                          Chào mừng đến với tiếng Việt, người ở đường Nguyễn Trãi";
+
+    #[test]
+    fn windows_1258_round_trip() {
+        let bytes = encode(SAMPLE, decode_windows_1258);
+        assert_eq!(decode_windows_1258(&bytes), SAMPLE);
+    }
+
+    #[test]
+    fn tcvn3_round_trip() {
+        let bytes = encode(SAMPLE, decode_tcvn3);
+        assert_eq!(decode_tcvn3(&bytes), SAMPLE);
+    }
+
+    #[test]
+    fn vni_round_trip() {
+        let bytes = encode(SAMPLE, decode_vni);
+        assert_eq!(decode_vni(&bytes), SAMPLE);
+    }
+
+    #[test]
+    fn decode_known_bytes() {
+        assert_eq!(decode_windows_1258(b"Vi\xea\xf2t Nam"), "Việt Nam");
+        assert_eq!(decode_windows_1258(b"\xd0\xf5\xecn"), "Đớn");
+        assert_eq!(decode_tcvn3(b"Vi\xd6t Nam"), "Việt Nam");
+        assert_eq!(decode_tcvn3(b"ng\xad\xeai"), "người");
+        assert_eq!(decode_vni(b"Vie\xe4t Nam"), "Việt Nam");
+        assert_eq!(decode_vni(b"ng\xf6\xf4\xf8i"), "người");
+        assert_eq!(decode_vni(b"VIE\xc4T"), "VIỆT");
+    }
+
+    #[test]
+    fn vni_mark_only_after_a_vowel() {
+        // `ä` after a consonant is the Latin-1 letter, not a mark
+        assert_eq!(decode_vni(b"t\xe4"), "tä");
+        assert_eq!(decode_vni(b"\xe4"), "ä");
+    }
+}
@@ -0,0 +1,185 @@
+mod legacy;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::parse::normalize_source;
+
+/// how the bytes of a source file are read, old school computers still save files with the
+/// Vietnamese fonts from before Unicode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1258,
+    Tcvn3,
+    Vni,
+}
+
+impl Encoding {
+    /// the value of `--encoding=`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "utf-16" | "utf-16le" | "utf16" => Some(Self::Utf16Le),
+            "utf-16be" => Some(Self::Utf16Be),
+            "windows-1258" | "cp1258" => Some(Self::Windows1258),
+            "tcvn3" | "abc" => Some(Self::Tcvn3),
+            "vni" | "vni-windows" => Some(Self::Vni),
+            _ => None,
+        }
+    }
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes.to_vec())
+                .map_err(|e| format!("tệp không phải là UTF-8 hợp lệ: {}", e)),
+            Self::Utf16Le | Self::Utf16Be => {
+                let units = bytes
+                    .chunks(2)
+                    .map(|c| {
+                        let pair = [c[0], *c.get(1).unwrap_or(&0)];
+                        if *self == Self::Utf16Le {
+                            u16::from_le_bytes(pair)
+                        } else {
+                            u16::from_be_bytes(pair)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                String::from_utf16(&units)
+                    .map_err(|_| "tệp không phải là UTF-16 hợp lệ".to_string())
+            }
+            Self::Windows1258 => Ok(legacy::decode_windows_1258(bytes)),
+            Self::Tcvn3 => Ok(legacy::decode_tcvn3(bytes)),
+            Self::Vni => Ok(legacy::decode_vni(bytes)),
+        }
+    }
+    /// the byte order mark tell the encoding, without it a file that is valid UTF-8 is
+    /// UTF-8 and the legacy encoding giving the most Vietnamese letters win
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0xFF, 0xFE]) {
+            return Self::Utf16Le;
+        }
+        if bytes.starts_with(&[0xFE, 0xFF]) {
+            return Self::Utf16Be;
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return Self::Utf8;
+        }
+        [Self::Windows1258, Self::Tcvn3, Self::Vni]
+            .into_iter()
+            .max_by_key(|e| e.decode(bytes).map_or(i64::MIN, |s| vietnamese_score(&s)))
+            .unwrap()
+    }
+}
+
+/// +1 for each letter outside ASCII in a word that can be Vietnamese, -1 for the others.
+/// A wrong guess give letters too but never one tone per word: `Tiờỡng`
+fn vietnamese_score(data: &str) -> i64 {
+    data.split(|c: char| !c.is_alphabetic())
+        .map(|word| {
+            let letters = word.chars().filter(|c| !c.is_ascii()).count() as i64;
+            let tones = word
+                .nfd()
+                .filter(|m| "\u{300}\u{301}\u{303}\u{309}\u{323}".contains(*m))
+                .count();
+            if tones <= 1 && word.chars().all(is_vietnamese_letter) {
+                letters
+            } else {
+                -letters
+            }
+        })
+        .sum()
+}
+
+fn is_vietnamese_letter(c: char) -> bool {
+    let mut parts = std::iter::once(c).nfd();
+    c.is_ascii_alphabetic()
+        || matches!(c, 'đ' | 'Đ')
+        || (parts.next().is_some_and(|b| b.is_ascii_alphabetic())
+            && parts
+                .all(|m| "\u{300}\u{301}\u{303}\u{309}\u{323}\u{302}\u{306}\u{31b}".contains(m)))
+}
+
+/// read a source file as the parser want it: UTF-8 without BOM, lines ending with `\n`
+/// and every word in NFC. A `\r` left by Windows would end up inside string literals
+pub fn load_source(bytes: &[u8], encoding: Option<Encoding>) -> Result<String, String> {
+    let encoding = encoding.unwrap_or_else(|| Encoding::detect(bytes));
+    let text = encoding.decode(bytes)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    Ok(normalize_source(
+        &text.replace("\r\n", "\n").replace('\r', "\n"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "## tuổi của người dùng\n\
+                           var tuổi = 20\n\
+                           in: \"Chào bạn, năm nay bạn bao nhiêu tuổi?\"\n\
+                           in: tuổi\n";
+
+    #[test]
+    fn detect_legacy_encodings() {
+        let legacy = [
+            (
+                Encoding::Windows1258,
+                legacy::decode_windows_1258 as fn(&[u8]) -> String,
+            ),
+            (Encoding::Tcvn3, legacy::decode_tcvn3),
+            (Encoding::Vni, legacy::decode_vni),
+        ];
+        for (encoding, decode) in legacy {
+            let bytes = legacy::tests::encode(PROGRAM, decode);
+            assert_eq!(Encoding::detect(&bytes), encoding);
+            assert_eq!(load_source(&bytes, None).unwrap(), PROGRAM);
+        }
+    }
+
+    #[test]
+    fn detect_unicode_encodings() {
+        assert_eq!(Encoding::detect(PROGRAM.as_bytes()), Encoding::Utf8);
+        let utf16 = |to_bytes: fn(u16) -> [u8; 2]| {
+            std::iter::once(0xFEFF)
+                .chain(PROGRAM.encode_utf16())
+                .flat_map(to_bytes)
+                .collect::<Vec<_>>()
+        };
+        for (bytes, encoding) in [
+            (utf16(u16::to_le_bytes), Encoding::Utf16Le),
+            (utf16(u16::to_be_bytes), Encoding::Utf16Be),
+        ] {
+            assert_eq!(Encoding::detect(&bytes), encoding);
+            assert_eq!(load_source(&bytes, None).unwrap(), PROGRAM);
+        }
+    }
+
+    #[test]
+    fn bom_and_crlf_are_removed() {
+        let windows = format!("\u{feff}{}", PROGRAM.replace('\n', "\r\n"));
+        assert_eq!(load_source(windows.as_bytes(), None).unwrap(), PROGRAM);
+        let old_mac = PROGRAM.replace('\n', "\r");
+        assert_eq!(load_source(old_mac.as_bytes(), None).unwrap(), PROGRAM);
+    }
+
+    #[test]
+    fn decomposed_letters_are_composed() {
+        let decomposed = PROGRAM.nfd().collect::<String>();
+        assert_eq!(load_source(decomposed.as_bytes(), None).unwrap(), PROGRAM);
+    }
+
+    #[test]
+    fn encoding_override() {
+        assert_eq!(Encoding::from_name("TCVN3"), Some(Encoding::Tcvn3));
+        assert_eq!(Encoding::from_name("vni_windows"), Some(Encoding::Vni));
+        assert_eq!(Encoding::from_name("cp1258"), Some(Encoding::Windows1258));
+        assert_eq!(Encoding::from_name("latin1"), None);
+        // the guess is skipped when `--encoding` is given
+        assert_eq!(
+            load_source(b"Vie\xe4t", Some(Encoding::Vni)).unwrap(),
+            "Việt"
+        );
+        assert!(load_source(b"Vie\xe4t", Some(Encoding::Utf8)).is_err());
+    }
+}
@@ -0,0 +1,820 @@
+use std::collections::BTreeMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::parse::{
+    parse_decimal_literal, walk_ast_mut, walk_expr, walk_expr_mut, walk_stmt, walk_stmt_mut, Ast,
+    BinaryOp, BuiltinFunction, Call, ExprId, ExprKind, KeywordConfig, RoundingMode, Span, Stmt,
+    StmtId, ValueType, Visitor, VisitorMut, DECIMAL_DIGITS, DECIMAL_SCALE,
+};
+
+#[derive(Debug, Clone)]
+pub struct TypeError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl TypeError {
+    pub fn new(span: Span, message: String) -> Self {
+        Self { span, message }
+    }
+    /// print the error with the source line under it like `rustc` does
+    pub fn report(&self, source: &str) -> String {
+        format!(
+            "lỗi kiểu ở dòng {}:{}: {}\n{}",
+            self.span.line,
+            self.span.start + 1,
+            self.message,
+            self.span.show(source)
+        )
+    }
+}
+
+/// check every statement before code generation, the declared type of each binding is
+/// written back to `Stmt::Let::value_type` so the compiler know which slot to allocate
+pub fn check_ast(ast: &mut Ast, config: &KeywordConfig) -> Result<(), Vec<TypeError>> {
+    let mut checker = TypeChecker {
+        config,
+        variable: Default::default(),
+        span: Default::default(),
+        errors: vec![],
+    };
+    walk_ast_mut(&mut checker, ast);
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct TypeChecker<'a> {
+    config: &'a KeywordConfig,
+    /// `None` when the binding exist but its type could not be inferred
+    variable: BTreeMap<String, Option<ValueType>>,
+    /// the statement being checked, the errors of its values point at it
+    span: Span,
+    errors: Vec<TypeError>,
+}
+
+impl VisitorMut for TypeChecker<'_> {
+    fn visit_stmt_mut(&mut self, ast: &mut Ast, id: StmtId) {
+        self.span = ast[id].span();
+        match &ast[id] {
+            Stmt::Let {
+                name,
+                annotation,
+                value,
+                span,
+                ..
+            } => {
+                let (name, annotation, value, span) =
+                    (name.clone(), annotation.clone(), *value, *span);
+                let result =
+                    self.check_new_variable(ast, &name, annotation.as_deref(), value, span);
+                if let Stmt::Let { value_type, .. } = &mut ast[id] {
+                    *value_type = result;
+                }
+                self.variable.insert(name, result);
+            }
+            Stmt::Assign {
+                name, value, span, ..
+            } => {
+                let (name, value, span) = (name.clone(), *value, *span);
+                let result = self.check_set_variable(ast, &name, value, span);
+                if let Stmt::Assign { value_type, .. } = &mut ast[id] {
+                    *value_type = result;
+                }
+            }
+            Stmt::Call { .. } => walk_stmt_mut(self, ast, id),
+            Stmt::Block { .. } => {
+                let variable = self.variable.clone();
+                walk_stmt_mut(self, ast, id);
+                self.variable = variable;
+                return;
+            }
+        }
+        // the types of the values are known now, their literals can be checked
+        let mut constants = ConstantCheck {
+            span: self.span,
+            errors: &mut self.errors,
+        };
+        walk_stmt(&mut constants, ast, id);
+    }
+
+    /// a value is inferred after its operands and arguments, the type of an operation
+    /// depend on theirs
+    fn visit_expr_mut(&mut self, ast: &mut Ast, id: ExprId) {
+        walk_expr_mut(self, ast, id);
+        self.infer_node(ast, id, self.span);
+    }
+}
+
+impl<'a> TypeChecker<'a> {
+    fn check_new_variable(
+        &mut self,
+        ast: &mut Ast,
+        variable_name: &str,
+        annotation: Option<&str>,
+        value: Option<ExprId>,
+        span: Span,
+    ) -> Option<ValueType> {
+        let written = annotation.is_some();
+        let annotation = match annotation {
+            Some(name) => match self.config.type_keyword.get(name) {
+                Some(t) => Some(*t),
+                None => {
+                    self.errors.push(TypeError::new(
+                        span,
+                        format!("không có kiểu nào tên là `{}`", name),
+                    ));
+                    None
+                }
+            },
+            None => None,
+        };
+        match value {
+            Some(value) => {
+                self.visit_expr_mut(ast, value);
+                let found = ast[value].value_type;
+                match annotation {
+                    Some(expected) => {
+                        self.check_assign(ast, expected, value, span, variable_name);
+                        Some(expected)
+                    }
+                    None => found,
+                }
+            }
+            // a number start at 0 and a string empty, a character or a list has no value
+            // to start with
+            None => match annotation {
+                Some(t @ (ValueType::Char | ValueType::List)) => {
+                    self.errors.push(TypeError::new(
+                        span,
+                        format!(
+                            "biến `{}` có kiểu {} phải có giá trị ban đầu",
+                            variable_name, t
+                        ),
+                    ));
+                    annotation
+                }
+                None if !written => {
+                    self.errors.push(TypeError::new(
+                        span,
+                        format!(
+                            "biến `{}` cần một kiểu hoặc một giá trị ban đầu",
+                            variable_name
+                        ),
+                    ));
+                    None
+                }
+                _ => annotation,
+            },
+        }
+    }
+
+    fn check_set_variable(
+        &mut self,
+        ast: &mut Ast,
+        variable_name: &str,
+        value: ExprId,
+        span: Span,
+    ) -> Option<ValueType> {
+        self.visit_expr_mut(ast, value);
+        let expected = match self.variable.get(variable_name) {
+            Some(Some(t)) => *t,
+            Some(None) => return None,
+            None => {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!("biến `{}` chưa được khai báo", variable_name),
+                ));
+                return None;
+            }
+        };
+        self.check_assign(ast, expected, value, span, variable_name);
+        Some(expected)
+    }
+
+    /// a number can be widened from int to float or decimal, every other type must match
+    /// exactly. Going from float to int lose data so it must be written as `số_nguyên(x)`
+    fn assignable(expected: ValueType, found: ValueType) -> bool {
+        expected == found
+            || (expected == ValueType::Float && found.is_int())
+            || (expected == ValueType::Decimal && found.is_int())
+            || (expected == ValueType::String && found == ValueType::Char)
+    }
+
+    /// an integer expression made only of literals take the integer type it is given to:
+    /// in `var x: số_nguyên_8 = 1 + 1` both literals and the sum are `số_nguyên_8`
+    fn check_assign(
+        &mut self,
+        ast: &mut Ast,
+        expected: ValueType,
+        value: ExprId,
+        span: Span,
+        variable_name: &str,
+    ) {
+        let found = match ast[value].value_type {
+            Some(t) => t,
+            None => return,
+        };
+        if expected.is_int() && found == ValueType::Int && is_int_literal(ast, value) {
+            adapt_int_literal(ast, value, expected);
+            return;
+        }
+        // `var c: kí_tự = "ệ"`, the literal must be exactly one character
+        if expected == ValueType::Char && found == ValueType::String {
+            if let ExprKind::String(s) = &ast[value].kind {
+                let count = s.graphemes(true).count();
+                if count != 1 {
+                    self.errors.push(TypeError::new(
+                        span,
+                        format!(
+                            "kí tự phải có đúng 1 kí tự nhưng \"{}\" có {} kí tự",
+                            s, count
+                        ),
+                    ));
+                }
+                ast[value].value_type = Some(ValueType::Char);
+                return;
+            }
+        }
+        // `var giá: thập_phân = 0.1 + 0.2` is computed exactly, not as float
+        if expected == ValueType::Decimal && found == ValueType::Float && is_literal(ast, value) {
+            self.adapt_decimal_literal(ast, value, span);
+            return;
+        }
+        if !Self::assignable(expected, found) {
+            self.errors.push(TypeError::new(
+                span,
+                format!(
+                    "không thể gán {} cho biến `{}` có kiểu {}",
+                    found, variable_name, expected
+                ),
+            ));
+        }
+    }
+
+    /// turn the float literals of an expression made only of literals into `thập_phân`
+    fn adapt_decimal_literal(&mut self, ast: &mut Ast, node: ExprId, span: Span) {
+        match ast[node].kind {
+            ExprKind::Float(f) if parse_decimal_literal(&f.to_string()).is_none() => {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "số {} quá lớn hoặc có nhiều hơn {} chữ số sau dấu chấm, không vừa với kiểu thập phân",
+                        f, DECIMAL_DIGITS
+                    ),
+                ));
+            }
+            ExprKind::Binary { left, right, .. } => {
+                self.adapt_decimal_literal(ast, left, span);
+                self.adapt_decimal_literal(ast, right, span);
+            }
+            _ => {}
+        }
+        ast[node].value_type = Some(ValueType::Decimal);
+    }
+
+    /// infer the type of an expression from the types already found for its operands and
+    /// write it to `value_type` so the compiler can pick the right LLVM type, signedness
+    /// and printf format for it
+    fn infer_node(&mut self, ast: &mut Ast, node: ExprId, span: Span) -> Option<ValueType> {
+        let result = match &ast[node].kind {
+            ExprKind::String(_) => Some(ValueType::String),
+            ExprKind::Int(_) => Some(ValueType::Int),
+            ExprKind::Float(_) => Some(ValueType::Float),
+            ExprKind::Decimal(_) => Some(ValueType::Decimal),
+            ExprKind::Bool(_) => Some(ValueType::Bool),
+            ExprKind::Variable(name) => self.variable.get(name.as_str()).copied().flatten(),
+            &ExprKind::Binary { op, left, right } => {
+                let operand = self.infer_math_operation(ast, left, right, node, span);
+                // the two sides are numbers of the same type, the result is a yes or no
+                if op.is_comparison() {
+                    operand.map(|_| ValueType::Bool)
+                } else {
+                    operand
+                }
+            }
+            ExprKind::Call(call) => {
+                let call = call.clone();
+                self.infer_call(ast, &call, span)
+            }
+            &ExprKind::Index { target, index } => {
+                self.infer_string_index(ast, target, &[index], true, span)
+            }
+            &ExprKind::Slice { target, start, end } => {
+                let mut positions = vec![start];
+                positions.extend(end);
+                self.infer_string_index(ast, target, &positions, false, span)
+            }
+        };
+        ast[node].value_type = result;
+        result
+    }
+
+    fn infer_call(&mut self, ast: &mut Ast, call: &Call, span: Span) -> Option<ValueType> {
+        let function_name = call.function.clone();
+        let function_type = self
+            .config
+            .default_function
+            .get(&function_name)
+            .map(|f| f.r#type.clone())
+            .unwrap_or_default();
+        if RoundingMode::from_config_type(&function_type).is_some() {
+            return self.infer_round_call(ast, &call.args, &function_name, span);
+        }
+        if let Some(builtin) = BuiltinFunction::from_config_type(&function_type) {
+            return self.infer_builtin_call(ast, &call.args, builtin, &function_name, span);
+        }
+        let target = match self.config.type_keyword.get(&function_name) {
+            Some(t) if t.is_number() => *t,
+            _ => {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "không có hàm nào tên là `{}` để dùng trong biểu thức",
+                        function_name
+                    ),
+                ));
+                return None;
+            }
+        };
+        if call.args.len() != 1 {
+            self.errors.push(TypeError::new(
+                span,
+                format!("`{}` cần đúng một giá trị", function_name),
+            ));
+            return Some(target);
+        }
+        if let Some(t) = ast[call.args[0]].value_type {
+            if !t.is_number() {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!("không thể đổi {} sang {}", t, target),
+                ));
+            }
+        }
+        Some(target)
+    }
+
+    fn infer_builtin_call(
+        &mut self,
+        ast: &mut Ast,
+        args: &[ExprId],
+        builtin: BuiltinFunction,
+        function_name: &str,
+        span: Span,
+    ) -> Option<ValueType> {
+        let parameters = builtin.parameters();
+        if args.len() != parameters.len() {
+            self.errors.push(TypeError::new(
+                span,
+                format!(
+                    "`{}` cần {} giá trị nhưng lại có {}",
+                    function_name,
+                    parameters.len(),
+                    args.len()
+                ),
+            ));
+        }
+        for (i, (&args, expected)) in args.iter().zip(parameters).enumerate() {
+            match ast[args].value_type {
+                Some(t) if !expected.contains(&t) => self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "giá trị thứ {} của `{}` phải là {} nhưng `{}` là {}",
+                        i + 1,
+                        function_name,
+                        expected
+                            .iter()
+                            .map(|t| t.to_string())
+                            .collect::<Vec<_>>()
+                            .join(" hoặc "),
+                        ast.raw(args),
+                        t
+                    ),
+                )),
+                _ => {}
+            }
+        }
+        Some(builtin.return_type())
+    }
+
+    /// `làm_tròn(x)` or `làm_tròn(x, 2)`, the number of digits must be written as a literal
+    fn infer_round_call(
+        &mut self,
+        ast: &mut Ast,
+        args: &[ExprId],
+        function_name: &str,
+        span: Span,
+    ) -> Option<ValueType> {
+        if args.is_empty() || args.len() > 2 {
+            self.errors.push(TypeError::new(
+                span,
+                format!(
+                    "`{}` cần một giá trị và số chữ số muốn giữ lại",
+                    function_name
+                ),
+            ));
+            return Some(ValueType::Decimal);
+        }
+        let found = ast[args[0]].value_type;
+        if found == Some(ValueType::Float) && is_literal(ast, args[0]) {
+            self.adapt_decimal_literal(ast, args[0], span);
+        } else if let Some(t) = found {
+            if t != ValueType::Decimal {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "`{}` chỉ dùng được cho thập phân, không dùng được cho {}, hãy dùng thập_phân(...) trước",
+                        function_name, t
+                    ),
+                ));
+            }
+        }
+        if let Some(&digits) = args.get(1) {
+            match int_literal(ast, digits) {
+                Some(d) if (0..=DECIMAL_DIGITS as i128).contains(&d) => {}
+                _ => self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "số chữ số của `{}` phải là một số từ 0 đến {}",
+                        function_name, DECIMAL_DIGITS
+                    ),
+                )),
+            }
+        }
+        Some(ValueType::Decimal)
+    }
+
+    /// `s[i]` give a `kí_tự` and `s[a..b]` a `chuỗi`, the positions count characters.
+    /// `ds[i]` give the string at `i` of a list
+    fn infer_string_index(
+        &mut self,
+        ast: &mut Ast,
+        target: ExprId,
+        positions: &[ExprId],
+        is_index: bool,
+        span: Span,
+    ) -> Option<ValueType> {
+        let target_type = ast[target].value_type;
+        match target_type {
+            Some(ValueType::String) | None => {}
+            Some(ValueType::List) if is_index => {}
+            Some(t) => self.errors.push(TypeError::new(
+                span,
+                format!(
+                    "chỉ lấy được kí tự của chuỗi, `{}` là {}",
+                    ast.raw(target),
+                    t
+                ),
+            )),
+        }
+        for &position in positions {
+            match ast[position].value_type {
+                Some(t) if !t.is_int() => self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "vị trí `{}` phải là số nguyên, không phải {}",
+                        ast.raw(position),
+                        t
+                    ),
+                )),
+                _ => {}
+            }
+        }
+        match target_type {
+            Some(ValueType::List) if is_index => Some(ValueType::String),
+            _ if is_index => Some(ValueType::Char),
+            _ => Some(ValueType::String),
+        }
+    }
+
+    fn infer_math_operation(
+        &mut self,
+        ast: &mut Ast,
+        left: ExprId,
+        right: ExprId,
+        node: ExprId,
+        span: Span,
+    ) -> Option<ValueType> {
+        let (left_type, right_type) = (ast[left].value_type, ast[right].value_type);
+        for (t, side) in [(left_type, left), (right_type, right)] {
+            if let Some(t) = t {
+                if !t.is_number() {
+                    self.errors.push(TypeError::new(
+                        span,
+                        format!("không thể tính toán với {} `{}`", t, ast.raw(side)),
+                    ));
+                    return None;
+                }
+            }
+        }
+        match (left_type?, right_type?) {
+            (l, r) if l == r => Some(l),
+            // an integer is widened exactly to decimal
+            (ValueType::Decimal, r) if r.is_int() => Some(ValueType::Decimal),
+            (l, ValueType::Decimal) if l.is_int() => Some(ValueType::Decimal),
+            // `giá * 1.1` where `giá` is a decimal, the literal become a decimal
+            (ValueType::Decimal, ValueType::Float) if is_literal(ast, right) => {
+                self.adapt_decimal_literal(ast, right, span);
+                Some(ValueType::Decimal)
+            }
+            (ValueType::Float, ValueType::Decimal) if is_literal(ast, left) => {
+                self.adapt_decimal_literal(ast, left, span);
+                Some(ValueType::Decimal)
+            }
+            // a float on either side promote the whole operation to float
+            (ValueType::Float, r) | (r, ValueType::Float) if r != ValueType::Decimal => {
+                Some(ValueType::Float)
+            }
+            // `a + 1` where `a` is a sized integer, the literal take the type of `a`
+            (l, ValueType::Int) if is_int_literal(ast, right) => {
+                adapt_int_literal(ast, right, l);
+                Some(l)
+            }
+            (ValueType::Int, r) if is_int_literal(ast, left) => {
+                adapt_int_literal(ast, left, r);
+                Some(r)
+            }
+            (l, r) => {
+                self.errors.push(TypeError::new(
+                    span,
+                    format!(
+                        "không thể tính {} với {} `{}`, hãy đổi kiểu cho giống nhau trước",
+                        l,
+                        r,
+                        ast.raw(node)
+                    ),
+                ));
+                None
+            }
+        }
+    }
+}
+
+fn int_literal(ast: &Ast, node: ExprId) -> Option<i128> {
+    match ast[node].kind {
+        ExprKind::Int(i) => Some(i),
+        _ => None,
+    }
+}
+
+/// `1 + 2 * 3`, an integer computed from integer literals only
+fn is_int_literal(ast: &Ast, node: ExprId) -> bool {
+    match ast[node].kind {
+        ExprKind::Int(_) => true,
+        ExprKind::Binary { op, left, right } => {
+            !op.is_comparison() && is_int_literal(ast, left) && is_int_literal(ast, right)
+        }
+        _ => false,
+    }
+}
+
+/// give every node of an integer literal expression the integer type `target`, whether
+/// the values fit is checked by `ConstantCheck`
+fn adapt_int_literal(ast: &mut Ast, node: ExprId, target: ValueType) {
+    if let ExprKind::Binary { left, right, .. } = ast[node].kind {
+        adapt_int_literal(ast, left, target);
+        adapt_int_literal(ast, right, target);
+    }
+    ast[node].value_type = Some(target);
+}
+
+/// the expression only use literals, so its value is known while compiling
+fn is_literal(ast: &Ast, node: ExprId) -> bool {
+    match ast[node].kind {
+        ExprKind::Int(_) | ExprKind::Float(_) | ExprKind::Decimal(_) => true,
+        ExprKind::Binary { left, right, .. } => is_literal(ast, left) && is_literal(ast, right),
+        _ => false,
+    }
+}
+
+/// run on a statement once its types are known. An integer literal must fit the type it
+/// was given, and `1 / 0` or `200 + 100` given to a `số_tự_nhiên_8` are computed by the
+/// compiler, the mistake is reported here instead of making a program that always stop
+struct ConstantCheck<'e> {
+    span: Span,
+    errors: &'e mut Vec<TypeError>,
+}
+
+impl<'ast> Visitor<'ast> for ConstantCheck<'_> {
+    fn visit_expr(&mut self, ast: &'ast Ast, id: ExprId) {
+        let before = self.errors.len();
+        walk_expr(self, ast, id);
+        // `200 + 100` in a `số_nguyên_8` already fail on `200`
+        if self.errors.len() != before {
+            return;
+        }
+        let Some(value_type) = ast[id].value_type else {
+            return;
+        };
+        let message = match ast[id].kind {
+            ExprKind::Int(literal) => match value_type.int_range() {
+                Some((min, max)) if literal < min || literal > max => {
+                    format!(
+                        "giá trị {} không vừa với kiểu {} (từ {} đến {})",
+                        literal, value_type, min, max
+                    )
+                }
+                _ => return,
+            },
+            ExprKind::Binary { op, left, right } => {
+                let (a, b) = match (
+                    constant_value(ast, left, value_type),
+                    constant_value(ast, right, value_type),
+                ) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => return,
+                };
+                match fold_constant(op, a, b, value_type) {
+                    Err(FoldError::DivideByZero) => {
+                        format!("không thể chia cho 0 ở `{}`", ast.raw(id))
+                    }
+                    Err(FoldError::Overflow) => format!(
+                        "phép tính `{}` bị tràn số, kết quả vượt quá giới hạn của kiểu {}",
+                        ast.raw(id),
+                        value_type
+                    ),
+                    Ok(_) | Err(FoldError::NotFolded) => return,
+                }
+            }
+            _ => return,
+        };
+        self.errors.push(TypeError::new(self.span, message));
+    }
+}
+
+enum FoldError {
+    /// the compiler leave the operation to the runtime, `*` and `/` of two decimals
+    NotFolded,
+    DivideByZero,
+    Overflow,
+}
+
+/// the value of an integer or `thập_phân` expression made only of literals, as it is used
+/// by an operation of type `target`: an integer used as a decimal is multiplied by
+/// `DECIMAL_SCALE` like the compiler do. `None` when it is not known while compiling
+fn constant_value(ast: &Ast, node: ExprId, target: ValueType) -> Option<i128> {
+    let value_type = ast[node].value_type?;
+    let value = match ast[node].kind {
+        ExprKind::Int(i) => i,
+        ExprKind::Decimal(d) => d as i128,
+        ExprKind::Float(f) if value_type == ValueType::Decimal => {
+            parse_decimal_literal(&f.to_string())? as i128
+        }
+        ExprKind::Binary { op, left, right } => {
+            let a = constant_value(ast, left, value_type)?;
+            let b = constant_value(ast, right, value_type)?;
+            fold_constant(op, a, b, value_type).ok()?
+        }
+        _ => return None,
+    };
+    if target == ValueType::Decimal && value_type.is_int() {
+        Some(value * DECIMAL_SCALE as i128)
+    } else {
+        Some(value)
+    }
+}
+
+/// `a op b` for two literals of type `value_type`, a `thập_phân` is an i64
+fn fold_constant(op: BinaryOp, a: i128, b: i128, value_type: ValueType) -> Result<i128, FoldError> {
+    let decimal = value_type == ValueType::Decimal;
+    if !decimal && !value_type.is_int() {
+        return Err(FoldError::NotFolded);
+    }
+    let result = match op {
+        BinaryOp::Plus => a + b,
+        BinaryOp::Minus => a - b,
+        BinaryOp::Divide | BinaryOp::Remainder if b == 0 => return Err(FoldError::DivideByZero),
+        // two `số_tự_nhiên_64` can give more than i128 hold
+        BinaryOp::Times if !decimal => a.checked_mul(b).ok_or(FoldError::Overflow)?,
+        BinaryOp::Divide if !decimal => a / b,
+        BinaryOp::Remainder => a % b,
+        _ => return Err(FoldError::NotFolded),
+    };
+    let (min, max) = value_type
+        .int_range()
+        .unwrap_or((i64::MIN as i128, i64::MAX as i128));
+    if result < min || result > max {
+        return Err(FoldError::Overflow);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_string_to_ast, test_pack};
+
+    fn check(source: &str) -> Result<Ast, Vec<String>> {
+        let config = test_pack("vi");
+        let mut ast = parse_string_to_ast(source.to_string(), &config).unwrap();
+        match check_ast(&mut ast, &config) {
+            Ok(()) => Ok(ast),
+            Err(errors) => Err(errors.into_iter().map(|e| e.message).collect()),
+        }
+    }
+
+    fn value_types(ast: &Ast) -> Vec<Option<ValueType>> {
+        struct Types(Vec<Option<ValueType>>);
+        impl<'ast> Visitor<'ast> for Types {
+            fn visit_expr(&mut self, ast: &'ast Ast, id: ExprId) {
+                self.0.push(ast[id].value_type);
+                walk_expr(self, ast, id);
+            }
+        }
+        let mut types = Types(vec![]);
+        crate::parse::walk_ast(&mut types, ast);
+        types.0
+    }
+
+    #[test]
+    fn literal_expressions_take_the_declared_type() {
+        let ast = check("var x: số_nguyên_8 = 1 + 1\n").unwrap();
+        assert_eq!(value_types(&ast), vec![Some(ValueType::Int8); 3]);
+        check("var x: số_nguyên_8 = -(5)\n").unwrap();
+        check("var x: số_nguyên_8 = -128\n").unwrap();
+        check("var x: số_tự_nhiên_8 = 200 + 55\n").unwrap();
+        check("var x: số_nguyên_16 = 100 * 300\n").unwrap();
+        check("var x: số_tự_nhiên_32 = 4294967295\n").unwrap();
+    }
+
+    #[test]
+    fn literal_out_of_range() {
+        assert_eq!(
+            check("var x: số_nguyên_8 = 128\n").unwrap_err(),
+            ["giá trị 128 không vừa với kiểu số nguyên 8 bit (từ -128 đến 127)"]
+        );
+        // `200` is already too big, the sum is not reported again
+        assert_eq!(
+            check("var x: số_nguyên_8 = 200 + 100\n").unwrap_err(),
+            ["giá trị 200 không vừa với kiểu số nguyên 8 bit (từ -128 đến 127)"]
+        );
+        assert_eq!(
+            check("var x: số_tự_nhiên_16 = 1\nvar y = x + 70000\n").unwrap_err(),
+            ["giá trị 70000 không vừa với kiểu số tự nhiên 16 bit (từ 0 đến 65535)"]
+        );
+    }
+
+    #[test]
+    fn literals_wider_than_64_bit_integer() {
+        let ast = check("var x: số_tự_nhiên_64 = 18446744073709551615\n").unwrap();
+        assert_eq!(value_types(&ast), vec![Some(ValueType::UInt64)]);
+        check("var x: số_tự_nhiên_64 = 9223372036854775807 + 9223372036854775808\n").unwrap();
+        check("var x = -9223372036854775808\n").unwrap();
+        assert_eq!(
+            check("var x = 9223372036854775808\n").unwrap_err(),
+            ["giá trị 9223372036854775808 không vừa với kiểu số nguyên (từ -9223372036854775808 đến 9223372036854775807)"]
+        );
+        assert_eq!(
+            check("var x: số_tự_nhiên_64 = 18446744073709551615 * 18446744073709551615\n")
+                .unwrap_err(),
+            ["phép tính `18446744073709551615 * 18446744073709551615` bị tràn số, kết quả vượt quá giới hạn của kiểu số tự nhiên 64 bit"]
+        );
+    }
+
+    #[test]
+    fn overflow_of_the_declared_type() {
+        assert_eq!(
+            check("var y: số_tự_nhiên_8 = 0 - 1\n").unwrap_err(),
+            ["phép tính `0 - 1` bị tràn số, kết quả vượt quá giới hạn của kiểu số tự nhiên 8 bit"]
+        );
+        assert_eq!(
+            check("var y: số_tự_nhiên_8 = 200 + 100\n").unwrap_err(),
+            ["phép tính `200 + 100` bị tràn số, kết quả vượt quá giới hạn của kiểu số tự nhiên 8 bit"]
+        );
+        assert_eq!(
+            check("var y: số_nguyên_32 = 2147483647 + 1\n").unwrap_err(),
+            ["phép tính `2147483647 + 1` bị tràn số, kết quả vượt quá giới hạn của kiểu số nguyên 32 bit"]
+        );
+        // the same value fit in the default 64 bit integer
+        check("var y = 2147483647 + 1\n").unwrap();
+        assert_eq!(
+            check("in: 9223372036854775807 + 1\n").unwrap_err(),
+            ["phép tính `9223372036854775807 + 1` bị tràn số, kết quả vượt quá giới hạn của kiểu số nguyên"]
+        );
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(
+            check("in: 1 / (2 - 2)\n").unwrap_err(),
+            ["không thể chia cho 0 ở `1 / (2 - 2)`"]
+        );
+    }
+
+    #[test]
+    fn sized_integers_do_not_mix() {
+        assert_eq!(
+            check("var a: số_nguyên_8 = 1\nvar b: số_tự_nhiên_8 = 1\nvar c = a + b\n")
+                .unwrap_err(),
+            ["không thể tính số nguyên 8 bit với số tự nhiên 8 bit `a + b`, hãy đổi kiểu cho giống nhau trước"]
+        );
+        assert_eq!(
+            check("var a: số_nguyên_16 = 1\nvar b: số_nguyên_8 = a\n").unwrap_err(),
+            ["không thể gán số nguyên 16 bit cho biến `b` có kiểu số nguyên 8 bit"]
+        );
+    }
+}
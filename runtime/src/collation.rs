@@ -0,0 +1,126 @@
+use std::{cmp::Ordering, ffi::c_char};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{
+    diacritics::split_tone,
+    list::{new_list, read_list, List},
+    text::read_string,
+};
+
+/// the Vietnamese alphabet with the letters only found in foreign words put where they are
+/// in the Latin one
+const ALPHABET: &str = "aăâbcdđeêfghijklmnoôơpqrstuưvwxyz";
+/// the order of tones in a dictionary: no tone, huyền, hỏi, ngã, sắc, nặng, indexed by the
+/// tone number of `split_tone`
+const TONE_ORDER: [u8; 6] = [0, 4, 1, 2, 3, 5];
+
+/// how a string is compared, level by level: the letters, then the tones, then the case
+struct CollationKey {
+    letters: Vec<u32>,
+    tones: Vec<u8>,
+    cases: Vec<bool>,
+}
+
+fn collation_key(data: &str) -> CollationKey {
+    let mut key = CollationKey {
+        letters: vec![],
+        tones: vec![],
+        cases: vec![],
+    };
+    for c in data.nfc() {
+        let (base, tone) = split_tone(c);
+        let lower = base.to_lowercase().next().unwrap_or(base);
+        // digits and punctuation come before every letter
+        let letter = match ALPHABET.chars().position(|l| l == lower) {
+            Some(i) => 0x11_0000 + i as u32,
+            None if lower.is_alphabetic() => {
+                0x11_0000 + ALPHABET.chars().count() as u32 + lower as u32
+            }
+            None => lower as u32,
+        };
+        key.letters.push(letter);
+        key.tones.push(TONE_ORDER[tone]);
+        key.cases.push(base.is_uppercase());
+    }
+    key
+}
+
+/// `Đức` come after `Dũng` and before `Em`, `Hà` come after `Ha` and before `Hả`
+pub(crate) fn compare(a: &str, b: &str) -> Ordering {
+    let (key_a, key_b) = (collation_key(a), collation_key(b));
+    key_a
+        .letters
+        .cmp(&key_b.letters)
+        .then_with(|| key_a.tones.cmp(&key_b.tones))
+        .then_with(|| key_a.cases.cmp(&key_b.cases))
+        .then_with(|| a.cmp(b))
+}
+
+/// `so_sánh_chuỗi(a, b)`: -1 when `a` come first, 0 when they are the same, 1 otherwise
+///
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_compare(a: *const c_char, b: *const c_char) -> i64 {
+    compare(read_string(a), read_string(b)) as i64
+}
+
+/// a sorted copy of the list
+///
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_sort(list: *const List) -> *mut List {
+    let mut data = read_list(list).0.clone();
+    data.sort_by(|a, b| compare(a, b));
+    new_list(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_in_alphabet_order() {
+        let letters = [
+            "a", "ă", "â", "b", "d", "đ", "e", "ê", "o", "ô", "ơ", "u", "ư",
+        ];
+        for pair in letters.windows(2) {
+            assert_eq!(compare(pair[0], pair[1]), Ordering::Less, "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn other_letters_after_the_alphabet() {
+        assert_eq!(compare("z", "ß"), Ordering::Less);
+        assert_eq!(compare("ß", "ø"), Ordering::Less);
+        assert_eq!(compare("9", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn letters_before_tones() {
+        assert_eq!(compare("Dũng", "Đức"), Ordering::Less);
+        assert_eq!(compare("Đức", "Em"), Ordering::Less);
+    }
+
+    #[test]
+    fn tones_in_dictionary_order() {
+        let words = ["ma", "mà", "mả", "mã", "má", "mạ"];
+        for pair in words.windows(2) {
+            assert_eq!(compare(pair[0], pair[1]), Ordering::Less, "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn tone_and_case_after_letters() {
+        assert_eq!(compare("Ha", "Hà"), Ordering::Less);
+        assert_eq!(compare("Hà", "Hả"), Ordering::Less);
+        // a tone written as a combining mark is read the same
+        assert_eq!(compare("Ha\u{300}", "Hả"), Ordering::Less);
+        assert_eq!(compare("ha", "Ha"), Ordering::Less);
+        assert_eq!(compare("Hà", "Hà"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,206 @@
+use std::ffi::c_char;
+
+use crate::fail;
+
+/// a `thập_phân` value is an i64 counting 1/10000, the same layout as the `MONEY` type of
+/// SQL Server. Exact for every VND amount up to about 922 nghìn tỷ đồng
+pub const DECIMAL_SCALE: i64 = 10_000;
+pub const DECIMAL_DIGITS: i32 = 4;
+/// size of the buffer the compiler give to `vipl_decimal_format`
+pub const DECIMAL_FORMAT_BUFFER_SIZE: usize = 32;
+
+/// must stay in the same order as `RoundingMode` in the compiler
+#[derive(Debug, Clone, Copy)]
+enum RoundingMode {
+    /// `làm_tròn`, .5 go away from zero like in school
+    HalfUp,
+    /// `làm_tròn_chẵn`, .5 go to the even neighbour, used by banks
+    HalfEven,
+    /// `làm_tròn_lên`
+    Ceiling,
+    /// `làm_tròn_xuống`
+    Floor,
+}
+
+impl RoundingMode {
+    fn from_raw(mode: i32) -> Self {
+        match mode {
+            0 => Self::HalfUp,
+            1 => Self::HalfEven,
+            2 => Self::Ceiling,
+            3 => Self::Floor,
+            _ => fail(&format!("lỗi: không có cách làm tròn số {}", mode)),
+        }
+    }
+}
+
+/// `numerator / denominator` rounded to an integer with `mode`
+fn divide_round(numerator: i128, denominator: i128, mode: RoundingMode) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return quotient;
+    }
+    let negative = (numerator < 0) != (denominator < 0);
+    let step = if negative { -1 } else { 1 };
+    let twice_remainder = remainder.abs() * 2;
+    match mode {
+        RoundingMode::HalfUp if twice_remainder >= denominator.abs() => quotient + step,
+        RoundingMode::HalfEven
+            if twice_remainder > denominator.abs()
+                || (twice_remainder == denominator.abs() && quotient % 2 != 0) =>
+        {
+            quotient + step
+        }
+        RoundingMode::Ceiling if !negative => quotient + 1,
+        RoundingMode::Floor if negative => quotient - 1,
+        _ => quotient,
+    }
+}
+
+fn to_decimal(value: i128) -> i64 {
+    i64::try_from(value).unwrap_or_else(|_| {
+        fail("lỗi: phép tính bị tràn số, kết quả vượt quá giới hạn của kiểu thập phân")
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_decimal_from_int(value: i64) -> i64 {
+    to_decimal(value as i128 * DECIMAL_SCALE as i128)
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_decimal_from_uint(value: u64) -> i64 {
+    to_decimal(value as i128 * DECIMAL_SCALE as i128)
+}
+
+/// the float is rounded to 4 digits, `thập_phân(0.1 + 0.2)` give exactly 0.3
+#[no_mangle]
+pub extern "C" fn vipl_decimal_from_float(value: f64) -> i64 {
+    let scaled = (value * DECIMAL_SCALE as f64).round_ties_even();
+    if !scaled.is_finite() || scaled < i64::MIN as f64 || scaled >= i64::MAX as f64 {
+        fail(&format!("lỗi: không thể đổi {} sang thập phân", value));
+    }
+    scaled as i64
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_decimal_to_float(value: i64) -> f64 {
+    value as f64 / DECIMAL_SCALE as f64
+}
+
+/// drop the fraction like `số_nguyên(2.9)` does for a float
+#[no_mangle]
+pub extern "C" fn vipl_decimal_to_int(value: i64) -> i64 {
+    value / DECIMAL_SCALE
+}
+
+/// the digits after the 4th are rounded half to even
+#[no_mangle]
+pub extern "C" fn vipl_decimal_mul(lhs: i64, rhs: i64) -> i64 {
+    to_decimal(divide_round(
+        lhs as i128 * rhs as i128,
+        DECIMAL_SCALE as i128,
+        RoundingMode::HalfEven,
+    ))
+}
+
+/// the digits after the 4th are rounded half to even
+#[no_mangle]
+pub extern "C" fn vipl_decimal_div(lhs: i64, rhs: i64) -> i64 {
+    if rhs == 0 {
+        fail("lỗi: không thể chia cho 0");
+    }
+    to_decimal(divide_round(
+        lhs as i128 * DECIMAL_SCALE as i128,
+        rhs as i128,
+        RoundingMode::HalfEven,
+    ))
+}
+
+/// keep `digits` digits after the dot, `mode` is a `RoundingMode`
+#[no_mangle]
+pub extern "C" fn vipl_decimal_round(value: i64, digits: i32, mode: i32) -> i64 {
+    let unit = 10i128.pow((DECIMAL_DIGITS - digits.clamp(0, DECIMAL_DIGITS)) as u32);
+    to_decimal(divide_round(value as i128, unit, RoundingMode::from_raw(mode)) * unit)
+}
+
+/// write `value` as text into `buffer` and return it, the trailing zeros of the
+/// fraction are not printed: `1.5000` is written `1.5`
+///
+/// # Safety
+///
+/// `buffer` must point to at least `DECIMAL_FORMAT_BUFFER_SIZE` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn vipl_decimal_format(value: i64, buffer: *mut c_char) -> *const c_char {
+    let sign = if value < 0 { "-" } else { "" };
+    let integer = value.unsigned_abs() / DECIMAL_SCALE as u64;
+    let fraction = value.unsigned_abs() % DECIMAL_SCALE as u64;
+    let text = if fraction == 0 {
+        format!("{}{}", sign, integer)
+    } else {
+        let fraction = format!("{:04}", fraction);
+        format!("{}{}.{}", sign, integer, fraction.trim_end_matches('0'))
+    };
+    let buffer = std::slice::from_raw_parts_mut(buffer as *mut u8, DECIMAL_FORMAT_BUFFER_SIZE);
+    buffer[..text.len()].copy_from_slice(text.as_bytes());
+    buffer[text.len()] = 0;
+    buffer.as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    fn format(value: i64) -> String {
+        let mut buffer = [0 as c_char; DECIMAL_FORMAT_BUFFER_SIZE];
+        unsafe { CStr::from_ptr(vipl_decimal_format(value, buffer.as_mut_ptr())) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn float_sum_is_exact() {
+        assert_eq!(vipl_decimal_from_float(0.1 + 0.2), 3000);
+        assert_eq!(
+            vipl_decimal_from_float(0.1) + vipl_decimal_from_float(0.2),
+            vipl_decimal_from_float(0.3)
+        );
+    }
+
+    #[test]
+    fn mul_div_round_half_even() {
+        assert_eq!(vipl_decimal_mul(15000, 25000), 37500);
+        // 0.0001 * 0.5 is 0.00005, the tie go to the even 0
+        assert_eq!(vipl_decimal_mul(1, 5000), 0);
+        assert_eq!(vipl_decimal_mul(3, 5000), 2);
+        assert_eq!(vipl_decimal_div(10000, 30000), 3333);
+        assert_eq!(vipl_decimal_div(20000, 30000), 6667);
+    }
+
+    #[test]
+    fn round_modes() {
+        let round = |value, mode: RoundingMode| vipl_decimal_round(value, 0, mode as i32);
+        assert_eq!(round(25000, RoundingMode::HalfUp), 30000);
+        assert_eq!(round(-25000, RoundingMode::HalfUp), -30000);
+        assert_eq!(round(25000, RoundingMode::HalfEven), 20000);
+        assert_eq!(round(35000, RoundingMode::HalfEven), 40000);
+        assert_eq!(round(-25001, RoundingMode::Ceiling), -20000);
+        assert_eq!(round(-25000, RoundingMode::Floor), -30000);
+        assert_eq!(
+            vipl_decimal_round(12345, 2, RoundingMode::HalfUp as i32),
+            12300
+        );
+    }
+
+    #[test]
+    fn format_without_trailing_zeros() {
+        assert_eq!(format(15000), "1.5");
+        assert_eq!(format(-500), "-0.05");
+        assert_eq!(format(20000), "2");
+        assert_eq!(format(i64::MIN), "-922337203685477.5808");
+    }
+}
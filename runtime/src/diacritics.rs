@@ -0,0 +1,417 @@
+use std::ffi::c_char;
+
+use unicode_normalization::{char::compose, UnicodeNormalization};
+
+use crate::text::{new_string, read_string};
+
+/// the combining mark of each tone, the index is the tone number used by VNI:
+/// 1 sắc, 2 huyền, 3 hỏi, 4 ngã, 5 nặng
+const TONE_MARKS: [char; 5] = ['\u{301}', '\u{300}', '\u{309}', '\u{303}', '\u{323}'];
+const TELEX_TONE_KEYS: [char; 5] = ['s', 'f', 'r', 'x', 'j'];
+const VOWELS: &str = "aăâeêioôơuưy";
+const MODIFIED_VOWELS: &str = "ăâêôơư";
+
+/// where the tone go in `oa`, `oe` and `uy` without a final consonant: `hòa` in the old
+/// style, `hoà` in the new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToneStyle {
+    Old,
+    New,
+}
+
+/// `ệ` → (`ê`, 5)
+pub(crate) fn split_tone(c: char) -> (char, usize) {
+    let mut tone = 0;
+    let mut base = String::new();
+    for d in c.to_string().nfd() {
+        match TONE_MARKS.iter().position(|m| *m == d) {
+            Some(i) => tone = i + 1,
+            None => base.push(d),
+        }
+    }
+    (base.nfc().next().unwrap_or(c), tone)
+}
+
+fn with_tone(c: char, tone: usize) -> char {
+    if tone == 0 {
+        return c;
+    }
+    // `ệ` is composed from `ẹ` and `^`, so `ê` and the dot below are put together by NFC
+    format!("{}{}", c, TONE_MARKS[tone - 1])
+        .nfc()
+        .next()
+        .unwrap_or(c)
+}
+
+fn is_vowel(c: char) -> bool {
+    c.to_lowercase().all(|c| VOWELS.contains(c))
+}
+
+fn is_modified_vowel(c: char) -> bool {
+    c.to_lowercase().all(|c| MODIFIED_VOWELS.contains(c))
+}
+
+/// put the tone of a word back on the right vowel, `letters` have no tone yet
+fn place_tone(letters: &[char], tone: usize, style: ToneStyle) -> String {
+    let lower = letters
+        .iter()
+        .flat_map(|c| c.to_lowercase())
+        .collect::<String>();
+    // the `u` of `qu` and the `i` of `gi` belong to the first consonant: `quả`, `già`
+    let onset_end = if lower.starts_with("qu")
+        || (lower.starts_with("gi") && letters.get(2).is_some_and(|c| is_vowel(*c)))
+    {
+        2
+    } else {
+        0
+    };
+    let start = match (onset_end..letters.len()).find(|i| is_vowel(letters[*i])) {
+        Some(start) if tone != 0 => start,
+        _ => return letters.iter().map(|c| with_tone(*c, tone)).collect(),
+    };
+    let end = (start..letters.len())
+        .find(|i| !is_vowel(letters[*i]))
+        .unwrap_or(letters.len());
+    let vowels = &letters[start..end];
+    let cluster = lower
+        .chars()
+        .skip(start)
+        .take(end - start)
+        .collect::<String>();
+    let has_final_consonant = end < letters.len();
+    let position = if let Some(i) = vowels.iter().rposition(|c| is_modified_vowel(*c)) {
+        i
+    } else if vowels.len() == 1 {
+        0
+    } else if has_final_consonant {
+        vowels.len() - 1
+    } else if vowels.len() == 3
+        || (matches!(cluster.as_str(), "oa" | "oe" | "uy") && style == ToneStyle::New)
+    {
+        1
+    } else {
+        0
+    };
+    letters
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i == start + position {
+                with_tone(*c, tone)
+            } else {
+                *c
+            }
+        })
+        .collect()
+}
+
+/// run `convert` on every word of `data`, the rest is copied as it is
+fn map_words(
+    data: &str,
+    is_word_char: fn(char) -> bool,
+    convert: impl Fn(&str) -> String,
+) -> String {
+    let mut result = String::new();
+    let mut word = String::new();
+    for c in data.nfc() {
+        if is_word_char(c) {
+            word.push(c);
+        } else {
+            if !word.is_empty() {
+                result.push_str(&convert(&word));
+                word.clear();
+            }
+            result.push(c);
+        }
+    }
+    if !word.is_empty() {
+        result.push_str(&convert(&word));
+    }
+    result
+}
+
+fn normalize_tone(data: &str, style: ToneStyle) -> String {
+    map_words(data, char::is_alphabetic, |word| {
+        let mut tone = 0;
+        let letters = word
+            .chars()
+            .map(|c| {
+                let (base, t) = split_tone(c);
+                if t != 0 {
+                    tone = t;
+                }
+                base
+            })
+            .collect::<Vec<_>>();
+        place_tone(&letters, tone, style)
+    })
+}
+
+/// `Nguyễn Văn Đức` → `Nguyen Van Duc`
+pub(crate) fn strip_diacritics(data: &str) -> String {
+    data.nfd()
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| match c {
+            'đ' => 'd',
+            'Đ' => 'D',
+            _ => c,
+        })
+        .collect()
+}
+
+/// `Việt` → `Vieetj`, the tone key is typed at the end of the word
+fn to_telex(data: &str) -> String {
+    map_words(data, char::is_alphabetic, |word| {
+        let mut result = String::new();
+        let mut tone = 0;
+        for c in word.chars() {
+            let (base, t) = split_tone(c);
+            if t != 0 {
+                tone = t;
+            }
+            let upper = base.is_uppercase();
+            let keys = match base.to_lowercase().next().unwrap_or(base) {
+                'â' => "aa",
+                'ă' => "aw",
+                'ê' => "ee",
+                'ô' => "oo",
+                'ơ' => "ow",
+                'ư' => "uw",
+                'đ' => "dd",
+                _ => {
+                    result.push(base);
+                    continue;
+                }
+            };
+            result.push_str(&if upper {
+                keys.to_uppercase()
+            } else {
+                keys.to_string()
+            });
+        }
+        if tone != 0 {
+            result.push(TELEX_TONE_KEYS[tone - 1]);
+        }
+        result
+    })
+}
+
+/// `Việt` → `Vie6t5`, the tone number is typed at the end of the word
+fn to_vni(data: &str) -> String {
+    map_words(data, char::is_alphabetic, |word| {
+        let mut result = String::new();
+        let mut tone = 0;
+        for c in word.chars() {
+            let (base, t) = split_tone(c);
+            if t != 0 {
+                tone = t;
+            }
+            let (letter, key) = match base {
+                'â' | 'Â' | 'ê' | 'Ê' | 'ô' | 'Ô' => (split_modifier(base), '6'),
+                'ơ' | 'Ơ' | 'ư' | 'Ư' => (split_modifier(base), '7'),
+                'ă' | 'Ă' => (split_modifier(base), '8'),
+                'đ' => ('d', '9'),
+                'Đ' => ('D', '9'),
+                _ => {
+                    result.push(base);
+                    continue;
+                }
+            };
+            result.push(letter);
+            result.push(key);
+        }
+        if tone != 0 {
+            result.push_str(&tone.to_string());
+        }
+        result
+    })
+}
+
+/// `ê` → `e`
+fn split_modifier(c: char) -> char {
+    c.to_string().nfd().next().unwrap_or(c)
+}
+
+/// add a mark (`^`, breve or horn) to a vowel, `None` when the vowel can not take it
+fn add_modifier(c: char, mark: char) -> Option<char> {
+    compose(c, mark)
+}
+
+const CIRCUMFLEX: char = '\u{302}';
+const BREVE: char = '\u{306}';
+const HORN: char = '\u{31b}';
+
+/// `Vieetj` → `Việt`, `nguwowif` → `người`
+fn from_telex(data: &str) -> String {
+    map_words(data, char::is_alphabetic, |word| {
+        let mut letters: Vec<char> = vec![];
+        let mut tone = 0;
+        for c in word.chars() {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            let last = letters.last().copied();
+            let last_lower = last.and_then(|l| l.to_lowercase().next());
+            let has_vowel = letters.iter().any(|l| is_vowel(*l));
+            match lower {
+                'd' if last_lower == Some('d') => {
+                    let upper = last.is_some_and(|l| l.is_uppercase());
+                    *letters.last_mut().unwrap() = if upper { 'Đ' } else { 'đ' };
+                }
+                'a' | 'e' | 'o' if last_lower == Some(lower) => {
+                    let l = letters.last_mut().unwrap();
+                    *l = add_modifier(*l, CIRCUMFLEX).unwrap_or(*l);
+                }
+                'w' => match last_lower {
+                    Some('a') => {
+                        let l = letters.last_mut().unwrap();
+                        *l = add_modifier(*l, BREVE).unwrap_or(*l);
+                    }
+                    Some('o') | Some('u') => {
+                        let i = letters.len() - 1;
+                        letters[i] = add_modifier(letters[i], HORN).unwrap_or(letters[i]);
+                        // `uow` is `ươ`
+                        if i > 0 && letters[i - 1].to_lowercase().next() == Some('u') {
+                            letters[i - 1] =
+                                add_modifier(letters[i - 1], HORN).unwrap_or(letters[i - 1]);
+                        }
+                    }
+                    _ => letters.push(if c.is_uppercase() { 'Ư' } else { 'ư' }),
+                },
+                // no Vietnamese word end with these letters, after a vowel they are tones
+                'z' if has_vowel => tone = 0,
+                _ if has_vowel && TELEX_TONE_KEYS.contains(&lower) => {
+                    tone = TELEX_TONE_KEYS.iter().position(|k| *k == lower).unwrap() + 1;
+                }
+                _ => letters.push(c),
+            }
+        }
+        place_tone(&letters, tone, ToneStyle::New)
+    })
+}
+
+/// `Vie6t5` → `Việt`, a number without a letter before it is kept: `2024`
+fn from_vni(data: &str) -> String {
+    map_words(data, char::is_alphanumeric, |word| {
+        let mut letters: Vec<char> = vec![];
+        let mut tone = 0;
+        for c in word.chars() {
+            let has_letter = letters.iter().any(|l| l.is_alphabetic());
+            let modify = |letters: &mut Vec<char>, targets: &str, mark: char| {
+                // the key can be typed after the end of the word: `Viet65`
+                match letters
+                    .iter()
+                    .rposition(|l| l.to_lowercase().all(|l| targets.contains(l)))
+                {
+                    Some(i) => {
+                        letters[i] = add_modifier(letters[i], mark).unwrap_or(letters[i]);
+                        // `uo7` is `ươ`
+                        if mark == HORN
+                            && i > 0
+                            && letters[i - 1].to_lowercase().next() == Some('u')
+                        {
+                            letters[i - 1] =
+                                add_modifier(letters[i - 1], HORN).unwrap_or(letters[i - 1]);
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            };
+            let used = has_letter
+                && match c {
+                    '0' => {
+                        tone = 0;
+                        true
+                    }
+                    '1'..='5' => {
+                        tone = c.to_digit(10).unwrap() as usize;
+                        true
+                    }
+                    '6' => modify(&mut letters, "aeo", CIRCUMFLEX),
+                    '7' => modify(&mut letters, "ou", HORN),
+                    '8' => modify(&mut letters, "a", BREVE),
+                    '9' => match letters.iter().rposition(|l| *l == 'd' || *l == 'D') {
+                        Some(i) => {
+                            letters[i] = if letters[i] == 'D' { 'Đ' } else { 'đ' };
+                            true
+                        }
+                        None => false,
+                    },
+                    _ => false,
+                };
+            if !used {
+                letters.push(c);
+            }
+        }
+        place_tone(&letters, tone, ToneStyle::New)
+    })
+}
+
+macro_rules! text_function {
+    ($name:ident, $function:expr) => {
+        /// # Safety
+        ///
+        /// `data` must be a NUL terminated string
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(data: *const c_char) -> *mut c_char {
+            let function: fn(&str) -> String = $function;
+            new_string(&function(read_string(data)))
+        }
+    };
+}
+
+text_function!(vipl_text_strip_diacritics, strip_diacritics);
+text_function!(vipl_text_tone_new_style, |s| normalize_tone(
+    s,
+    ToneStyle::New
+));
+text_function!(vipl_text_tone_old_style, |s| normalize_tone(
+    s,
+    ToneStyle::Old
+));
+text_function!(vipl_text_from_telex, from_telex);
+text_function!(vipl_text_to_telex, to_telex);
+text_function!(vipl_text_from_vni, from_vni);
+text_function!(vipl_text_to_vni, to_vni);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// written with the tones of the new style, the one given back by `from_telex` and
+    /// `from_vni`
+    const SAMPLE: &str = "Người Việt Nam hoà bình, quả già, Đức ăn trưa lúc 12 giờ";
+
+    #[test]
+    fn telex() {
+        assert_eq!(to_telex("Việt"), "Vieetj");
+        assert_eq!(from_telex("Vieetj"), "Việt");
+        assert_eq!(from_telex("nguwowif"), "người");
+        assert_eq!(from_telex("DDuwcs"), "Đức");
+        assert_eq!(from_telex(&to_telex(SAMPLE)), SAMPLE);
+    }
+
+    #[test]
+    fn vni() {
+        assert_eq!(to_vni("Việt"), "Vie6t5");
+        assert_eq!(from_vni("Vie6t5"), "Việt");
+        // the keys can be typed after the end of the word
+        assert_eq!(from_vni("Viet65"), "Việt");
+        assert_eq!(from_vni("nguoi72"), "người");
+        assert_eq!(from_vni("nam 2024"), "nam 2024");
+        assert_eq!(from_vni(&to_vni(SAMPLE)), SAMPLE);
+    }
+
+    #[test]
+    fn tone_styles() {
+        assert_eq!(normalize_tone("hòa thủy", ToneStyle::New), "hoà thuỷ");
+        assert_eq!(normalize_tone("hoà thuỷ", ToneStyle::Old), "hòa thủy");
+        // with a final consonant the tone is on the last vowel in both styles
+        assert_eq!(normalize_tone("hoàng", ToneStyle::Old), "hoàng");
+        assert_eq!(normalize_tone("quả già", ToneStyle::Old), "quả già");
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_diacritics("Nguyễn Văn Đức"), "Nguyen Van Duc");
+    }
+}
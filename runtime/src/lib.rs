@@ -0,0 +1,21 @@
+//! functions the compiled program call for the work that is too big to emit as LLVM IR,
+//! every one of them is `extern "C"` and start with `vipl_`
+mod collation;
+mod decimal;
+mod diacritics;
+mod list;
+mod text;
+mod words;
+
+pub use collation::*;
+pub use decimal::*;
+pub use diacritics::*;
+pub use list::*;
+pub use text::*;
+pub use words::*;
+
+/// stop the program the same way the checks emitted by the compiler do
+pub(crate) fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
@@ -0,0 +1,107 @@
+use std::ffi::c_char;
+
+use crate::{
+    fail,
+    text::{characters, new_string, read_string},
+};
+
+/// a `danh_sách`, the compiled program only hold a pointer to it
+pub struct List(pub(crate) Vec<String>);
+
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+pub(crate) unsafe fn read_list<'a>(list: *const List) -> &'a List {
+    &*list
+}
+
+pub(crate) fn new_list(data: Vec<String>) -> *mut List {
+    Box::into_raw(Box::new(List(data)))
+}
+
+/// `var b = a` where `a` is a list, see `vipl_string_copy`
+///
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_copy(list: *const List) -> *mut List {
+    new_list(read_list(list).0.clone())
+}
+
+/// a list is freed with this and not with `free`, its strings are owned by the `Vec`
+///
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions and must not be used after
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_free(list: *mut List) {
+    drop(Box::from_raw(list));
+}
+
+/// `tách("a, b", ", ")`, an empty separator split into characters
+///
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_split(
+    data: *const c_char,
+    separator: *const c_char,
+) -> *mut List {
+    let data = read_string(data);
+    let separator = read_string(separator);
+    new_list(if separator.is_empty() {
+        characters(data).into_iter().map(String::from).collect()
+    } else {
+        data.split(separator).map(String::from).collect()
+    })
+}
+
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions and `separator` must be a NUL
+/// terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_join(
+    list: *const List,
+    separator: *const c_char,
+) -> *mut c_char {
+    new_string(&read_list(list).0.join(read_string(separator)))
+}
+
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_length(list: *const List) -> i64 {
+    read_list(list).0.len() as i64
+}
+
+/// `ds[index]`, a copy of the string
+///
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_get(list: *const List, index: i64) -> *mut c_char {
+    let list = &read_list(list).0;
+    match usize::try_from(index).ok().and_then(|i| list.get(i)) {
+        Some(s) => new_string(s),
+        None => fail(&format!(
+            "lỗi: vị trí {} nằm ngoài danh sách có {} phần tử",
+            index,
+            list.len()
+        )),
+    }
+}
+
+/// the text `in` print for a list: `[An, Bình]`
+///
+/// # Safety
+///
+/// `list` must come from one of the `vipl_` functions
+#[no_mangle]
+pub unsafe extern "C" fn vipl_list_format(list: *const List) -> *mut c_char {
+    new_string(&format!("[{}]", read_list(list).0.join(", ")))
+}
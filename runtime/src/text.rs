@@ -0,0 +1,185 @@
+use std::ffi::{c_char, c_void, CStr};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::fail;
+
+/// `s[1..]` is compiled with this as the end
+pub const SLICE_TO_END: i64 = i64::MAX;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+}
+
+/// read a string made by the compiled program
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+pub(crate) unsafe fn read_string<'a>(data: *const c_char) -> &'a str {
+    CStr::from_ptr(data)
+        .to_str()
+        .unwrap_or_else(|_| fail("lỗi: chuỗi không phải là UTF-8"))
+}
+
+/// copy `data` to memory from `malloc`, the compiled program free it like its own strings
+pub(crate) fn new_string(data: &str) -> *mut c_char {
+    unsafe {
+        let result = malloc(data.len() + 1) as *mut u8;
+        if result.is_null() {
+            fail("lỗi: hết bộ nhớ");
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), result, data.len());
+        *result.add(data.len()) = 0;
+        result as *mut c_char
+    }
+}
+
+/// a character is a grapheme cluster: `ệ` is one character even when it is written as
+/// `e` followed by two combining marks
+pub(crate) fn characters(data: &str) -> Vec<&str> {
+    data.graphemes(true).collect()
+}
+
+/// `var t = s`, the new variable own its own string so freeing one does not touch the
+/// other
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_copy(data: *const c_char) -> *mut c_char {
+    new_string(read_string(data))
+}
+
+/// `s[index]`, counted in characters from 0
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_char_at(data: *const c_char, index: i64) -> *mut c_char {
+    let characters = characters(read_string(data));
+    match usize::try_from(index).ok().and_then(|i| characters.get(i)) {
+        Some(c) => new_string(c),
+        None => fail(&format!(
+            "lỗi: vị trí {} nằm ngoài chuỗi có {} kí tự",
+            index,
+            characters.len()
+        )),
+    }
+}
+
+/// `s[start..end]`, the character at `end` is not included
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_slice(
+    data: *const c_char,
+    start: i64,
+    end: i64,
+) -> *mut c_char {
+    let characters = characters(read_string(data));
+    let length = characters.len() as i64;
+    let end = if end == SLICE_TO_END { length } else { end };
+    if start < 0 || end > length || start > end {
+        fail(&format!(
+            "lỗi: đoạn {}..{} nằm ngoài chuỗi có {} kí tự",
+            start, end, length
+        ));
+    }
+    new_string(&characters[start as usize..end as usize].concat())
+}
+
+/// number of characters, not bytes: `độ_dài("Việt")` is 4
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_length(data: *const c_char) -> i64 {
+    read_string(data).graphemes(true).count() as i64
+}
+
+/// `đ` become `Đ` and every vowel keep its tone mark, `ệ` become `Ệ`, also when the
+/// mark is written as a combining character
+///
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_upper(data: *const c_char) -> *mut c_char {
+    new_string(&read_string(data).to_uppercase())
+}
+
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_lower(data: *const c_char) -> *mut c_char {
+    new_string(&read_string(data).to_lowercase())
+}
+
+/// # Safety
+///
+/// `data` must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_trim(data: *const c_char) -> *mut c_char {
+    new_string(read_string(data).trim())
+}
+
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_replace(
+    data: *const c_char,
+    from: *const c_char,
+    to: *const c_char,
+) -> *mut c_char {
+    let from = read_string(from);
+    if from.is_empty() {
+        return new_string(read_string(data));
+    }
+    new_string(&read_string(data).replace(from, read_string(to)))
+}
+
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_contains(data: *const c_char, part: *const c_char) -> i32 {
+    read_string(data).contains(read_string(part)) as i32
+}
+
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_starts_with(data: *const c_char, part: *const c_char) -> i32 {
+    read_string(data).starts_with(read_string(part)) as i32
+}
+
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_ends_with(data: *const c_char, part: *const c_char) -> i32 {
+    read_string(data).ends_with(read_string(part)) as i32
+}
+
+/// position of the first `part` counted in characters like `s[i]`, -1 when not found
+///
+/// # Safety
+///
+/// every argument must be a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn vipl_string_find(data: *const c_char, part: *const c_char) -> i64 {
+    let data = read_string(data);
+    match data.find(read_string(part)) {
+        Some(byte) => data[..byte].graphemes(true).count() as i64,
+        None => -1,
+    }
+}
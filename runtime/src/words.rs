@@ -0,0 +1,187 @@
+use std::ffi::c_char;
+
+use crate::{
+    decimal::{DECIMAL_DIGITS, DECIMAL_SCALE},
+    fail,
+    text::new_string,
+};
+
+const DIGITS: [&str; 10] = [
+    "không", "một", "hai", "ba", "bốn", "năm", "sáu", "bảy", "tám", "chín",
+];
+/// the names of the groups of 3 digits below 1 tỷ, bigger numbers repeat `tỷ`:
+/// 10^18 is `một tỷ tỷ`
+const GROUPS: [&str; 3] = ["", "nghìn", "triệu"];
+
+/// a group of 3 digits, `full` when a bigger group was read before it: 1005 is
+/// `một nghìn không trăm linh năm`. The zero tens are read `linh` like on invoices, the
+/// South often say `lẻ`
+fn read_group(value: usize, full: bool, words: &mut Vec<&'static str>) {
+    let (hundreds, tens, units) = (value / 100, value / 10 % 10, value % 10);
+    if hundreds > 0 || full {
+        words.extend([DIGITS[hundreds], "trăm"]);
+    }
+    match tens {
+        0 if units > 0 => {
+            if hundreds > 0 || full {
+                words.push("linh");
+            }
+            words.push(DIGITS[units]);
+        }
+        0 => {}
+        1 => words.push("mười"),
+        _ => words.extend([DIGITS[tens], "mươi"]),
+    }
+    if tens > 0 {
+        match units {
+            0 => {}
+            // `mười lăm`, `hai mươi lăm`
+            5 => words.push("lăm"),
+            // `hai mươi mốt` but `mười một`
+            1 if tens > 1 => words.push("mốt"),
+            // `hai mươi tư` but `mười bốn`
+            4 if tens > 1 => words.push("tư"),
+            _ => words.push(DIGITS[units]),
+        }
+    }
+}
+
+/// `digits` is a number below 1 tỷ without leading zeros
+fn read_below_billion(digits: &str, full: bool, words: &mut Vec<&'static str>) {
+    let value = digits.parse::<usize>().unwrap_or(0);
+    let mut full = full;
+    for (i, name) in GROUPS.iter().enumerate().rev() {
+        let group = value / 1000usize.pow(i as u32) % 1000;
+        if group == 0 {
+            continue;
+        }
+        read_group(group, full, words);
+        if !name.is_empty() {
+            words.push(name);
+        }
+        full = true;
+    }
+}
+
+/// `digits` is a non zero number of any size without leading zeros
+fn read_digits(digits: &str, full: bool, words: &mut Vec<&'static str>) {
+    if digits.len() > 9 {
+        let (high, low) = digits.split_at(digits.len() - 9);
+        read_digits(high, full, words);
+        words.push("tỷ");
+        read_below_billion(low, true, words);
+    } else {
+        read_below_billion(digits, full, words);
+    }
+}
+
+/// `integer` and `fraction` are ASCII digits, `fraction` is read after `phẩy` with its
+/// leading zeros: 12.05 is `mười hai phẩy không năm`
+fn read_number(negative: bool, integer: &str, fraction: &str) -> String {
+    let mut words = vec![];
+    if negative {
+        words.push("âm");
+    }
+    match integer.trim_start_matches('0') {
+        "" => words.push(DIGITS[0]),
+        digits => read_digits(digits, false, &mut words),
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if !fraction.is_empty() {
+        words.push("phẩy");
+        let digits = fraction.trim_start_matches('0');
+        words.extend(std::iter::repeat_n(
+            DIGITS[0],
+            fraction.len() - digits.len(),
+        ));
+        read_digits(digits, false, &mut words);
+    }
+    words.join(" ")
+}
+
+/// `đọc_số(123456)` is `một trăm hai mươi ba nghìn bốn trăm năm mươi sáu`
+#[no_mangle]
+pub extern "C" fn vipl_number_words_int(value: i64) -> *mut c_char {
+    new_string(&read_number(
+        value < 0,
+        &value.unsigned_abs().to_string(),
+        "",
+    ))
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_number_words_uint(value: u64) -> *mut c_char {
+    new_string(&read_number(false, &value.to_string(), ""))
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_number_words_float(value: f64) -> *mut c_char {
+    if !value.is_finite() {
+        fail(&format!("lỗi: không đọc được số {}", value));
+    }
+    // `{}` never use the scientific notation, 1e20 is written with all its zeros
+    let text = value.abs().to_string();
+    let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    new_string(&read_number(value < 0.0, integer, fraction))
+}
+
+#[no_mangle]
+pub extern "C" fn vipl_number_words_decimal(value: i64) -> *mut c_char {
+    let absolute = value.unsigned_abs();
+    let scale = DECIMAL_SCALE as u64;
+    new_string(&read_number(
+        value < 0,
+        &(absolute / scale).to_string(),
+        &format!(
+            "{:0width$}",
+            absolute % scale,
+            width = DECIMAL_DIGITS as usize
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(value: i64) -> String {
+        read_number(value < 0, &value.unsigned_abs().to_string(), "")
+    }
+
+    #[test]
+    fn read_zero_tens_as_linh() {
+        assert_eq!(words(1005), "một nghìn không trăm linh năm");
+        assert_eq!(words(105), "một trăm linh năm");
+        assert_eq!(words(5), "năm");
+    }
+
+    #[test]
+    fn read_negative() {
+        assert_eq!(words(-21), "âm hai mươi mốt");
+    }
+
+    #[test]
+    fn read_units_after_tens() {
+        assert_eq!(words(11), "mười một");
+        assert_eq!(words(14), "mười bốn");
+        assert_eq!(words(15), "mười lăm");
+        assert_eq!(words(24), "hai mươi tư");
+        assert_eq!(words(25), "hai mươi lăm");
+    }
+
+    #[test]
+    fn read_big_numbers() {
+        assert_eq!(
+            words(123456),
+            "một trăm hai mươi ba nghìn bốn trăm năm mươi sáu"
+        );
+        assert_eq!(words(2_000_000_001), "hai tỷ không trăm linh một");
+        assert_eq!(words(1_000_000_000_000_000_000), "một tỷ tỷ");
+    }
+
+    #[test]
+    fn read_fraction_with_leading_zeros() {
+        assert_eq!(read_number(false, "12", "0500"), "mười hai phẩy không năm");
+        assert_eq!(read_number(false, "0", "5"), "không phẩy năm");
+    }
+}